@@ -0,0 +1,345 @@
+// Head-to-head numbers for the crate's lists against the std collections.
+//
+// Criterion keeps results under target/criterion, so runs can be compared
+// across commits:
+//   cargo bench --bench lists -- --save-baseline before
+//   (switch commits)
+//   cargo bench --bench lists -- --baseline before
+//
+// Each operation runs at several sizes with a cheap element (u64) and one
+// that owns heap memory (String).
+
+use std::collections::{LinkedList, VecDeque};
+
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+use lists::{deque_book, shared_list, sll};
+
+const SIZES: &[usize] = &[16, 1_000, 10_000];
+
+trait Elem: Clone {
+    const NAME: &'static str;
+    fn make(i: usize) -> Self;
+}
+
+impl Elem for u64 {
+    const NAME: &'static str = "u64";
+    fn make(i: usize) -> Self {
+        i as u64
+    }
+}
+
+impl Elem for String {
+    const NAME: &'static str = "string";
+    fn make(i: usize) -> Self {
+        format!("element {}", i)
+    }
+}
+
+/********** Builders **********/
+
+fn sll_of<E: Elem>(n: usize) -> sll::List<E> {
+    let mut list = sll::List::new();
+    for i in 0..n {
+        list.push(E::make(i));
+    }
+    list
+}
+
+fn shared_of<E: Elem>(n: usize) -> shared_list::List<E> {
+    (0..n).fold(shared_list::List::new(), |list, i| list.append(E::make(i)))
+}
+
+fn deque_of<E: Elem>(n: usize) -> deque_book::List<E> {
+    let mut list = deque_book::List::new();
+    for i in 0..n {
+        list.push_back(E::make(i));
+    }
+    list
+}
+
+fn vec_of<E: Elem>(n: usize) -> Vec<E> {
+    (0..n).map(E::make).collect()
+}
+
+fn vecdeque_of<E: Elem>(n: usize) -> VecDeque<E> {
+    (0..n).map(E::make).collect()
+}
+
+fn linked_of<E: Elem>(n: usize) -> LinkedList<E> {
+    (0..n).map(E::make).collect()
+}
+
+// Touch every item so the optimizer can't skip the walk (or, for Vec, turn
+// it into a len()).
+fn walk<I: IntoIterator>(iter: I) {
+    for x in iter {
+        black_box(x);
+    }
+}
+
+/********** Benchmarks **********/
+
+// Push n elements onto the front, then pop them all off again.
+fn push_pop_front<E: Elem>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("push_pop_front/{}", E::NAME));
+    for &n in SIZES {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::new("sll", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = sll::List::new();
+                for i in 0..n {
+                    list.push(E::make(i));
+                }
+                while list.pop().is_some() {}
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("shared_list", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = shared_list::List::new();
+                for i in 0..n {
+                    list = list.append(E::make(i));
+                }
+                while list.head().is_some() {
+                    list = list.tail();
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("deque_book", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = deque_book::List::new();
+                for i in 0..n {
+                    list.push_front(E::make(i));
+                }
+                while list.pop_front().is_some() {}
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("VecDeque", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = VecDeque::new();
+                for i in 0..n {
+                    list.push_front(E::make(i));
+                }
+                while list.pop_front().is_some() {}
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("LinkedList", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = LinkedList::new();
+                for i in 0..n {
+                    list.push_front(E::make(i));
+                }
+                while list.pop_front().is_some() {}
+            })
+        });
+    }
+    group.finish();
+}
+
+// Same at the back. sll and shared_list only have one end.
+fn push_pop_back<E: Elem>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("push_pop_back/{}", E::NAME));
+    for &n in SIZES {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::new("deque_book", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = deque_book::List::new();
+                for i in 0..n {
+                    list.push_back(E::make(i));
+                }
+                while list.pop_back().is_some() {}
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("Vec", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = Vec::new();
+                for i in 0..n {
+                    list.push(E::make(i));
+                }
+                while list.pop().is_some() {}
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("VecDeque", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = VecDeque::new();
+                for i in 0..n {
+                    list.push_back(E::make(i));
+                }
+                while list.pop_back().is_some() {}
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("LinkedList", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = LinkedList::new();
+                for i in 0..n {
+                    list.push_back(E::make(i));
+                }
+                while list.pop_back().is_some() {}
+            })
+        });
+    }
+    group.finish();
+}
+
+// Walk every element by reference. deque_book hands out a Ref per element.
+fn iter<E: Elem>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("iter/{}", E::NAME));
+    for &n in SIZES {
+        group.throughput(Throughput::Elements(n as u64));
+        let list = sll_of::<E>(n);
+        group.bench_function(BenchmarkId::new("sll", n), |b| b.iter(|| walk(list.iter())));
+        let list = shared_of::<E>(n);
+        group.bench_function(BenchmarkId::new("shared_list", n), |b| {
+            b.iter(|| walk(list.iter()))
+        });
+        let list = deque_of::<E>(n);
+        group.bench_function(BenchmarkId::new("deque_book", n), |b| {
+            b.iter(|| walk(list.iter()))
+        });
+        let list = vec_of::<E>(n);
+        group.bench_function(BenchmarkId::new("Vec", n), |b| b.iter(|| walk(list.iter())));
+        let list = vecdeque_of::<E>(n);
+        group.bench_function(BenchmarkId::new("VecDeque", n), |b| {
+            b.iter(|| walk(list.iter()))
+        });
+        let list = linked_of::<E>(n);
+        group.bench_function(BenchmarkId::new("LinkedList", n), |b| {
+            b.iter(|| walk(list.iter()))
+        });
+    }
+    group.finish();
+}
+
+// Consume the list front to back by value.
+fn drain<E: Elem>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("drain/{}", E::NAME));
+    for &n in SIZES {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::new("sll", n), |b| {
+            b.iter_batched(|| sll_of::<E>(n), walk, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("deque_book", n), |b| {
+            b.iter_batched(|| deque_of::<E>(n), walk, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("Vec", n), |b| {
+            b.iter_batched(|| vec_of::<E>(n), walk, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("VecDeque", n), |b| {
+            b.iter_batched(|| vecdeque_of::<E>(n), walk, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("LinkedList", n), |b| {
+            b.iter_batched(|| linked_of::<E>(n), walk, BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+// shared_list's clone is O(1) structure sharing; everyone else copies.
+fn clone<E: Elem>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("clone/{}", E::NAME));
+    for &n in SIZES {
+        group.throughput(Throughput::Elements(n as u64));
+        let list = sll_of::<E>(n);
+        group.bench_function(BenchmarkId::new("sll", n), |b| b.iter(|| list.clone()));
+        let list = shared_of::<E>(n);
+        group.bench_function(BenchmarkId::new("shared_list", n), |b| {
+            b.iter(|| list.clone())
+        });
+        let list = deque_of::<E>(n);
+        group.bench_function(BenchmarkId::new("deque_book", n), |b| {
+            b.iter(|| list.clone())
+        });
+        let list = vec_of::<E>(n);
+        group.bench_function(BenchmarkId::new("Vec", n), |b| b.iter(|| list.clone()));
+        let list = vecdeque_of::<E>(n);
+        group.bench_function(BenchmarkId::new("VecDeque", n), |b| b.iter(|| list.clone()));
+        let list = linked_of::<E>(n);
+        group.bench_function(BenchmarkId::new("LinkedList", n), |b| {
+            b.iter(|| list.clone())
+        });
+    }
+    group.finish();
+}
+
+fn drop_list<E: Elem>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("drop/{}", E::NAME));
+    for &n in SIZES {
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::new("sll", n), |b| {
+            b.iter_batched(|| sll_of::<E>(n), drop, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("shared_list", n), |b| {
+            b.iter_batched(|| shared_of::<E>(n), drop, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("deque_book", n), |b| {
+            b.iter_batched(|| deque_of::<E>(n), drop, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("Vec", n), |b| {
+            b.iter_batched(|| vec_of::<E>(n), drop, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("VecDeque", n), |b| {
+            b.iter_batched(|| vecdeque_of::<E>(n), drop, BatchSize::LargeInput)
+        });
+        group.bench_function(BenchmarkId::new("LinkedList", n), |b| {
+            b.iter_batched(|| linked_of::<E>(n), drop, BatchSize::LargeInput)
+        });
+    }
+    group.finish();
+}
+
+// A queue workload: for every element, push two at the back and pop one at
+// the front, then drain what's left.
+fn mixed<E: Elem>(c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("mixed/{}", E::NAME));
+    for &n in SIZES {
+        group.throughput(Throughput::Elements(3 * n as u64));
+        group.bench_with_input(BenchmarkId::new("deque_book", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = deque_book::List::new();
+                for i in 0..n {
+                    list.push_back(E::make(i));
+                    list.push_back(E::make(i));
+                    list.pop_front();
+                }
+                while list.pop_front().is_some() {}
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("VecDeque", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = VecDeque::new();
+                for i in 0..n {
+                    list.push_back(E::make(i));
+                    list.push_back(E::make(i));
+                    list.pop_front();
+                }
+                while list.pop_front().is_some() {}
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("LinkedList", n), &n, |b, &n| {
+            b.iter(|| {
+                let mut list = LinkedList::new();
+                for i in 0..n {
+                    list.push_back(E::make(i));
+                    list.push_back(E::make(i));
+                    list.pop_front();
+                }
+                while list.pop_front().is_some() {}
+            })
+        });
+    }
+    group.finish();
+}
+
+fn all<E: Elem>(c: &mut Criterion) {
+    push_pop_front::<E>(c);
+    push_pop_back::<E>(c);
+    iter::<E>(c);
+    drain::<E>(c);
+    clone::<E>(c);
+    drop_list::<E>(c);
+    mixed::<E>(c);
+}
+
+criterion_group!(benches, all::<u64>, all::<String>);
+criterion_main!(benches);
@@ -0,0 +1,72 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lists::deque_book::List;
+
+const OPS: usize = 10_000;
+
+// A queue that stays short but churns: every push is matched by a pop, which
+// is where recycling nodes pays off.
+fn churn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pool/churn");
+    group.throughput(Throughput::Elements(OPS as u64));
+
+    for &depth in &[1, 16, 256] {
+        group.bench_with_input(BenchmarkId::new("no_pool", depth), &depth, |b, &depth| {
+            let mut list = List::new();
+            for i in 0..depth {
+                list.push_back(i);
+            }
+            b.iter(|| {
+                for i in 0..OPS {
+                    list.push_back(i);
+                    list.pop_front();
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("pool", depth), &depth, |b, &depth| {
+            let mut list = List::with_pool(64);
+            for i in 0..depth {
+                list.push_back(i);
+            }
+            b.iter(|| {
+                for i in 0..OPS {
+                    list.push_back(i);
+                    list.pop_front();
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+// Fill up and drain completely, so the pool has to absorb a whole burst.
+fn burst(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pool/burst");
+    group.throughput(Throughput::Elements(OPS as u64));
+
+    group.bench_function("no_pool", |b| {
+        let mut list = List::new();
+        b.iter(|| {
+            for i in 0..OPS {
+                list.push_back(i);
+            }
+            while list.pop_front().is_some() {}
+        })
+    });
+
+    group.bench_function("pool", |b| {
+        let mut list = List::with_pool(OPS);
+        b.iter(|| {
+            for i in 0..OPS {
+                list.push_back(i);
+            }
+            while list.pop_front().is_some() {}
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, churn, burst);
+criterion_main!(benches);
@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use lists::deque_book;
+use lists::ms_queue::Queue;
+
+const OPS: usize = 10_000;
+
+fn uncontended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("queue/uncontended");
+    group.throughput(Throughput::Elements(OPS as u64));
+
+    group.bench_function("ms_queue", |b| {
+        let queue = Queue::new();
+        b.iter(|| {
+            for i in 0..OPS {
+                queue.push(i);
+            }
+            while queue.try_pop().is_some() {}
+        })
+    });
+
+    group.bench_function("mutex_deque_book", |b| {
+        let list = Mutex::new(deque_book::List::new());
+        b.iter(|| {
+            for i in 0..OPS {
+                list.lock().unwrap().push_back(i);
+            }
+            while list.lock().unwrap().pop_front().is_some() {}
+        })
+    });
+
+    group.finish();
+}
+
+// deque_book::List is built on Rc, so it cannot be shared across threads even
+// behind a Mutex. Mutex<VecDeque> stands in for it when producers scale up.
+fn producers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("queue/producers");
+    group.throughput(Throughput::Elements(OPS as u64));
+
+    for &n in &[1, 2, 4, 8] {
+        group.bench_with_input(BenchmarkId::new("ms_queue", n), &n, |b, &n| {
+            b.iter(|| {
+                let queue = Arc::new(Queue::new());
+                let handles: Vec<_> = (0..n)
+                    .map(|_| {
+                        let queue = queue.clone();
+                        thread::spawn(move || {
+                            for i in 0..OPS / n {
+                                queue.push(i);
+                            }
+                        })
+                    })
+                    .collect();
+                let mut popped = 0;
+                while popped < OPS / n * n {
+                    if queue.try_pop().is_some() {
+                        popped += 1;
+                    }
+                }
+                for h in handles {
+                    h.join().unwrap();
+                }
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("mutex_vecdeque", n), &n, |b, &n| {
+            b.iter(|| {
+                let queue = Arc::new(Mutex::new(VecDeque::new()));
+                let handles: Vec<_> = (0..n)
+                    .map(|_| {
+                        let queue = queue.clone();
+                        thread::spawn(move || {
+                            for i in 0..OPS / n {
+                                queue.lock().unwrap().push_back(i);
+                            }
+                        })
+                    })
+                    .collect();
+                let mut popped = 0;
+                while popped < OPS / n * n {
+                    if queue.lock().unwrap().pop_front().is_some() {
+                        popped += 1;
+                    }
+                }
+                for h in handles {
+                    h.join().unwrap();
+                }
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, uncontended, producers);
+criterion_main!(benches);
@@ -0,0 +1,125 @@
+// Each owned list's sort_by and par_sort_by against the usual workaround of
+// copying the list into a Vec, running rayon's par_sort and rebuilding the
+// list. Needs the rayon feature for the Vec side:
+//   cargo bench --bench sort --features rayon
+//
+// Every run sorts a fresh, pseudo-randomly ordered list, so building it is
+// left out of the timings.
+
+use criterion::{
+    criterion_group, criterion_main, BatchSize, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+use criterion::measurement::WallTime;
+use lists::xor_list::XorList;
+use lists::{deque_book, sll};
+use rayon::slice::ParallelSliceMut;
+
+const SIZES: &[usize] = &[10_000, 100_000, 1_000_000];
+
+// xorshift, so runs are comparable without pulling in rand.
+fn scrambled(n: usize) -> Vec<u64> {
+    let mut x: u64 = 0x9e37_79b9_7f4a_7c15;
+    (0..n)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        })
+        .collect()
+}
+
+// The three contenders for one list type, `build` making it from the
+// elements front to back.
+fn bench_list<L>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    n: usize,
+    build: impl Fn(Vec<u64>) -> L,
+    sort: impl Fn(&mut L),
+    par_sort: impl Fn(&mut L),
+    into_vec: impl Fn(L) -> Vec<u64>,
+) {
+    group.bench_function(BenchmarkId::new(format!("{} sort_by", name), n), |b| {
+        b.iter_batched(
+            || build(scrambled(n)),
+            |mut list| {
+                sort(&mut list);
+                list
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function(BenchmarkId::new(format!("{} par_sort_by", name), n), |b| {
+        b.iter_batched(
+            || build(scrambled(n)),
+            |mut list| {
+                par_sort(&mut list);
+                list
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function(BenchmarkId::new(format!("{} via Vec par_sort", name), n), |b| {
+        b.iter_batched(
+            || build(scrambled(n)),
+            |list| {
+                let mut elems = into_vec(list);
+                elems.par_sort();
+                build(elems)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn sort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort/u64");
+    group.sample_size(10);
+    for &n in SIZES {
+        group.throughput(Throughput::Elements(n as u64));
+        bench_list(
+            &mut group,
+            "sll",
+            n,
+            |elems| {
+                let mut list = sll::List::new();
+                for elem in elems.into_iter().rev() {
+                    list.push(elem);
+                }
+                list
+            },
+            |list| list.sort_by(|a, b| a.cmp(b)),
+            |list| list.par_sort_by(|a, b| a.cmp(b)),
+            |list| list.into_iter().collect(),
+        );
+        bench_list(
+            &mut group,
+            "deque_book",
+            n,
+            |elems| {
+                let mut list = deque_book::List::new();
+                for elem in elems {
+                    list.push_back(elem);
+                }
+                list
+            },
+            |list| list.sort_by(|a, b| a.cmp(b)),
+            |list| list.par_sort_by(|a, b| a.cmp(b)),
+            |list| list.into_iter().collect(),
+        );
+        bench_list(
+            &mut group,
+            "xor_list",
+            n,
+            |elems| elems.into_iter().collect::<XorList<u64>>(),
+            |list| list.sort_by(|a, b| a.cmp(b)),
+            |list| list.par_sort_by(|a, b| a.cmp(b)),
+            |list| list.into_iter().collect(),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, sort);
+criterion_main!(benches);
@@ -0,0 +1,290 @@
+#![no_main]
+
+use std::collections::VecDeque;
+use std::ffi::c_char;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use lists::deque_book::{List, ListError};
+use lists_fuzz::{Budget, Op};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    pool_limit: u8,
+    ops: Vec<Op>,
+}
+
+fn contents(list: &List<u16, Budget>) -> Vec<u16> {
+    list.clone().into_iter().collect()
+}
+
+fn list_of(elems: &[u16], alloc: &Budget) -> List<u16, Budget> {
+    let mut list = List::new_in(alloc.clone());
+    for &elem in elems {
+        list.push_back(elem);
+    }
+    list
+}
+
+// Keeps LeakSanitizer quiet about what `stuck` leaks on purpose.
+#[no_mangle]
+pub extern "C" fn __lsan_default_suppressions() -> *const c_char {
+    b"leak:deque_book::stuck\n\0".as_ptr().cast()
+}
+
+// libfuzzer-sys aborts from its panic hook, so the panics these checks expect
+// are caught with a silent hook in its place.
+fn panics(f: impl FnOnce()) -> bool {
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let panicked = panic::catch_unwind(AssertUnwindSafe(f)).is_err();
+    panic::set_hook(hook);
+    panicked
+}
+
+type Pop = fn(&mut List<u16>) -> Option<u16>;
+type TryPop = fn(&mut List<u16>) -> Result<Option<u16>, ListError>;
+
+// Op::Stuck. Leaks a borrow of one end of a copy of the list, which is what
+// holding a Ref across a pop would do if the borrow checker allowed it, then
+// checks that whatever needs that node refuses: the try_* calls with
+// Borrowed, the rest by panicking, and either way the copy still matches the
+// model. Nothing public hands out a node handle, so SharedNode can't come up
+// here.
+//
+// The last two nodes can't be parted once one is stuck, so the copy leaks. It
+// lives on Global and is built in here, so the suppression above covers it
+// and nothing else.
+#[inline(never)]
+fn stuck(model: &VecDeque<u16>, pool_limit: usize, how: u8, arg: u16) {
+    if model.is_empty() {
+        return;
+    }
+    let mut model = model.clone();
+    let mut list = List::with_pool(pool_limit);
+    for &elem in &model {
+        list.push_back(elem);
+    }
+    let front = how % 2 == 0;
+    let exclusive = how / 2 % 3 == 1;
+    match (how / 2 % 3, front) {
+        (0, true) => mem::forget(list.peek_front()),
+        (0, false) => mem::forget(list.peek_back()),
+        (1, true) => mem::forget(list.peek_front_mut()),
+        (1, false) => mem::forget(list.peek_back_mut()),
+        (_, true) => mem::forget(list.iter().next()),
+        (_, false) => mem::forget(list.iter().next_back()),
+    }
+    let (pop_near, try_pop_near, try_pop_far): (Pop, TryPop, TryPop) = if front {
+        (List::pop_front, List::try_pop_front, List::try_pop_back)
+    } else {
+        (List::pop_back, List::try_pop_back, List::try_pop_front)
+    };
+
+    assert_eq!(try_pop_near(&mut list), Err(ListError::Borrowed));
+    assert!(panics(|| {
+        pop_near(&mut list);
+    }));
+    assert!(panics(|| match front {
+        true => list.push_front(arg),
+        false => list.push_back(arg),
+    }));
+    assert!(panics(|| list.sort_by(|a, b| a.cmp(b))));
+
+    // A lone node is both ends.
+    let (front_stuck, back_stuck) = (front || model.len() == 1, !front || model.len() == 1);
+    let mut other = List::new();
+    other.push_back(arg);
+    assert_eq!(panics(|| list.append(&mut other)), back_stuck);
+    if back_stuck {
+        assert_eq!(other.pop_front(), Some(arg));
+    } else {
+        model.push_back(arg);
+    }
+
+    // split_off borrows the nodes either side of the cut mutably, and the
+    // ones before it shared on the way there.
+    let n = model.len();
+    let at = usize::from(arg) % (n + 1);
+    let b = if front { 0 } else { n - 1 };
+    let refused = at >= 1 && (at - 1 == b || at == b || (exclusive && b + 2 <= at));
+    let mut rest = None;
+    assert_eq!(panics(|| rest = Some(list.split_off(at))), refused);
+    if let Some(mut rest) = rest {
+        // The stuck node isn't at the cut, so this can't panic either.
+        list.append(&mut rest);
+    }
+
+    let peeks = [
+        (list.try_peek_front().map(|e| e.map(|e| *e)), front_stuck, model.front()),
+        (list.try_peek_back().map(|e| e.map(|e| *e)), back_stuck, model.back()),
+    ];
+    for (got, stuck, want) in peeks {
+        match stuck && exclusive {
+            true => assert_eq!(got, Err(ListError::Borrowed)),
+            false => assert_eq!(got, Ok(want.copied())),
+        }
+    }
+    assert_eq!(list.try_peek_front_mut().is_err(), front_stuck);
+    assert_eq!(list.try_peek_back_mut().is_err(), back_stuck);
+    // Iterators only panic on reaching a mutably borrowed node.
+    let unstuck = n - 1;
+    if front {
+        let want = model.iter().rev().take(unstuck);
+        assert!(list.iter().rev().take(unstuck).map(|e| *e).eq(want.copied()));
+    } else {
+        let want = model.iter().take(unstuck);
+        assert!(list.iter().take(unstuck).map(|e| *e).eq(want.copied()));
+    }
+    assert_eq!(panics(|| {
+        list.iter().count();
+    }), exclusive);
+
+    while model.len() > 2 {
+        let want = if front { model.pop_back() } else { model.pop_front() };
+        assert_eq!(try_pop_far(&mut list), Ok(want));
+    }
+    // Popping the last node but one borrows its neighbour, the stuck one.
+    assert_eq!(try_pop_far(&mut list), Err(ListError::Borrowed));
+}
+
+fuzz_target!(|input: Input| {
+    let alloc = Budget::new(usize::MAX);
+    let pool_limit = usize::from(input.pool_limit % 16);
+    let mut list = List::with_pool_in(pool_limit, alloc.clone());
+    let mut model = VecDeque::new();
+
+    for op in input.ops {
+        match op {
+            Op::PushFront(elem) => {
+                list.push_front(elem);
+                model.push_front(elem);
+            }
+            Op::PushBack(elem) => {
+                list.push_back(elem);
+                model.push_back(elem);
+            }
+            Op::TryPushFront(elem, room) | Op::TryPushBack(elem, room) => {
+                let front = matches!(op, Op::TryPushFront(..));
+                // A pooled node is reused without asking the allocator.
+                let fits = room || list.pooled() > 0;
+                alloc.set(if room { 1 } else { 0 });
+                let res = if front {
+                    list.try_push_front(elem)
+                } else {
+                    list.try_push_back(elem)
+                };
+                alloc.set(usize::MAX);
+                assert_eq!(res.is_ok(), fits);
+                match res {
+                    Ok(()) if front => model.push_front(elem),
+                    Ok(()) => model.push_back(elem),
+                    Err((back, _)) => assert_eq!(back, elem),
+                }
+            }
+            Op::PopFront => assert_eq!(list.pop_front(), model.pop_front()),
+            Op::PopBack => assert_eq!(list.pop_back(), model.pop_back()),
+            Op::TryPopFront => assert_eq!(list.try_pop_front(), Ok(model.pop_front())),
+            Op::TryPopBack => assert_eq!(list.try_pop_back(), Ok(model.pop_back())),
+            Op::PeekFront => {
+                assert_eq!(list.peek_front().map(|elem| *elem), model.front().copied());
+                assert_eq!(
+                    list.try_peek_front().unwrap().map(|elem| *elem),
+                    model.front().copied()
+                );
+            }
+            Op::PeekBack => {
+                assert_eq!(list.peek_back().map(|elem| *elem), model.back().copied());
+                assert_eq!(
+                    list.try_peek_back().unwrap().map(|elem| *elem),
+                    model.back().copied()
+                );
+            }
+            Op::PeekFrontMut(elem) => match (list.peek_front_mut(), model.front_mut()) {
+                (Some(mut got), Some(want)) => {
+                    *got = elem;
+                    *want = elem;
+                }
+                (got, want) => assert!(got.is_none() && want.is_none()),
+            },
+            Op::PeekBackMut(elem) => match (list.peek_back_mut(), model.back_mut()) {
+                (Some(mut got), Some(want)) => {
+                    *got = elem;
+                    *want = elem;
+                }
+                (got, want) => assert!(got.is_none() && want.is_none()),
+            },
+            Op::Iter => assert!(contents(&list).iter().eq(model.iter())),
+            Op::Clone => {
+                let copy = list.clone();
+                assert_eq!(contents(&list), contents(&copy));
+                list = copy;
+            }
+            Op::SplitOff(at) => {
+                let at = usize::from(at) % (model.len() + 1);
+                let rest = list.split_off(at);
+                let rest: Vec<_> = rest.into_iter().collect();
+                assert_eq!(rest, Vec::from(model.split_off(at)));
+            }
+            Op::Append(elems) => {
+                let mut other = list_of(&elems, &alloc);
+                list.append(&mut other);
+                assert!(other.pop_front().is_none());
+                model.extend(elems);
+            }
+            Op::Splice(at, elems) => {
+                let at = usize::from(at) % (model.len() + 1);
+                let mut rest = list.split_off(at);
+                list.append(&mut list_of(&elems, &alloc));
+                list.append(&mut rest);
+                for (i, elem) in elems.into_iter().enumerate() {
+                    model.insert(at + i, elem);
+                }
+            }
+            Op::Retain(n) => {
+                list.retain(|&elem| Op::keeps(n, elem));
+                model.retain(|&elem| Op::keeps(n, elem));
+            }
+            Op::Sort => {
+                list.sort_by(|a, b| a.cmp(b));
+                model.make_contiguous().sort();
+            }
+            // No iter_mut on a RefCell list: edit each element through
+            // peek_front_mut and rotate it round to the back.
+            Op::IterMut(delta) => {
+                for _ in 0..model.len() {
+                    let mut front = list.peek_front_mut().unwrap();
+                    *front = front.wrapping_add(delta);
+                    drop(front);
+                    let elem = list.pop_front().unwrap();
+                    list.push_back(elem);
+                }
+                for elem in model.iter_mut() {
+                    *elem = elem.wrapping_add(delta);
+                }
+            }
+            Op::ShrinkPool => {
+                list.shrink_to_fit();
+                assert_eq!(list.pooled(), 0);
+            }
+            Op::Stuck(how, arg) => stuck(&model, pool_limit, how, arg),
+            Op::Reverse | Op::Advance(_) | Op::Retreat | Op::SetMark => {}
+        }
+        assert!(list.pooled() <= pool_limit);
+    }
+
+    // Drain from both ends so every unlink path runs.
+    let mut iter = list.into_iter();
+    loop {
+        let (got, want) = match model.len() % 2 {
+            0 => (iter.next(), model.pop_front()),
+            _ => (iter.next_back(), model.pop_back()),
+        };
+        assert_eq!(got, want);
+        if got.is_none() {
+            break;
+        }
+    }
+});
@@ -0,0 +1,56 @@
+#![no_main]
+
+use std::collections::VecDeque;
+
+use libfuzzer_sys::fuzz_target;
+use lists::ms_queue::Queue;
+use lists_fuzz::{all_once, race, Op, Tagged, THREADS};
+
+// Whatever one thread saw of another's pushes has to be in push order.
+fn in_push_order(seen: &[Tagged]) {
+    for thread in 0..THREADS {
+        let from: Vec<_> = seen.iter().filter(|elem| elem.0 == thread).collect();
+        assert!(from.windows(2).all(|pair| pair[0].1 < pair[1].1));
+    }
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    // One thread, against a VecDeque.
+    let queue = Queue::new();
+    let mut model = VecDeque::new();
+    for op in &ops {
+        match *op {
+            Op::PushFront(elem) | Op::PushBack(elem) => {
+                queue.push(elem);
+                model.push_back(elem);
+            }
+            Op::PopFront | Op::PopBack => assert_eq!(queue.try_pop(), model.pop_front()),
+            _ => {}
+        }
+        assert_eq!(queue.len(), model.len());
+        assert_eq!(queue.is_empty(), model.is_empty());
+    }
+    // Drop whatever is left with the queue half the time.
+    if ops.len() % 2 == 0 {
+        while let Some(elem) = queue.try_pop() {
+            assert_eq!(Some(elem), model.pop_front());
+        }
+        assert!(model.is_empty());
+    }
+    drop(queue);
+
+    // Several threads at once: nothing lost, nothing popped twice, and each
+    // thread's pushes come out in order.
+    let queue = Queue::new();
+    let (popped, pushes) = race(&ops, &queue, Queue::push, Queue::try_pop);
+    let mut rest = Vec::new();
+    while let Some(elem) = queue.try_pop() {
+        rest.push(elem);
+    }
+    assert!(queue.is_empty());
+    in_push_order(&rest);
+    for seen in &popped {
+        in_push_order(seen);
+    }
+    all_once(popped.into_iter().flatten().chain(rest).collect(), pushes);
+});
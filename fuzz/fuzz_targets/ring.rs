@@ -0,0 +1,101 @@
+#![no_main]
+
+use std::collections::VecDeque;
+
+use libfuzzer_sys::fuzz_target;
+use lists::ring::Ring;
+use lists_fuzz::Op;
+
+// The model is one lap starting at the current element. Each element carries
+// a unique id so the mark can be followed through the edits.
+fuzz_target!(|ops: Vec<Op>| {
+    let mut ring = Ring::new();
+    let mut model: VecDeque<(u32, u16)> = VecDeque::new();
+    let mut mark: Option<u32> = None;
+    let mut next_id = 0;
+    let mut tag = |elem: u16| {
+        next_id += 1;
+        (next_id, elem)
+    };
+
+    for op in ops {
+        match op {
+            // At the current element, which the new one becomes.
+            Op::PushFront(elem) => {
+                let elem = tag(elem);
+                ring.insert_here(elem);
+                model.push_front(elem);
+            }
+            // At the end of the lap.
+            Op::PushBack(elem) => {
+                let elem = tag(elem);
+                ring.push_back(elem);
+                model.push_back(elem);
+            }
+            Op::PopFront => {
+                let got = ring.remove_here();
+                assert_eq!(got, model.pop_front());
+                if got.map(|(id, _)| id) == mark {
+                    mark = None;
+                }
+            }
+            Op::PeekFront => assert_eq!(ring.current(), model.front()),
+            Op::PeekFrontMut(elem) => match (ring.current_mut(), model.front_mut()) {
+                (Some(got), Some(want)) => {
+                    got.1 = elem;
+                    want.1 = elem;
+                }
+                (got, want) => assert!(got.is_none() && want.is_none()),
+            },
+            Op::Iter => assert!(ring.iter().eq(model.iter())),
+            Op::Advance(n) => {
+                ring.advance_by(usize::from(n));
+                if !model.is_empty() {
+                    let n = usize::from(n) % model.len();
+                    model.rotate_left(n);
+                }
+            }
+            Op::Retreat => {
+                ring.retreat();
+                if !model.is_empty() {
+                    model.rotate_right(1);
+                }
+            }
+            Op::SetMark => {
+                ring.set_mark();
+                mark = model.front().map(|&(id, _)| id);
+            }
+            // Cuts out the span from the mark up to the current element.
+            Op::SplitOff(_) => {
+                let rest = ring.split();
+                let at = mark
+                    .take()
+                    .and_then(|mark| model.iter().position(|&(id, _)| id == mark))
+                    .filter(|&at| at > 0);
+                match at {
+                    Some(at) => assert!(rest.into_iter().eq(model.split_off(at))),
+                    None => assert!(rest.is_empty()),
+                }
+            }
+            Op::Append(elems) => {
+                let elems: Vec<_> = elems.into_iter().map(&mut tag).collect();
+                let mut other: Ring<_> = elems.iter().copied().collect();
+                other.set_mark();
+                ring.join(&mut other);
+                assert!(other.is_empty());
+                model.extend(elems);
+            }
+            // Cuts the span out and joins it back on at the end of the lap,
+            // where it already was, so nothing moves.
+            Op::Splice(..) => {
+                let mut rest = ring.split();
+                ring.join(&mut rest);
+                mark = None;
+            }
+            _ => {}
+        }
+        assert_eq!(ring.is_empty(), model.is_empty());
+    }
+
+    assert!(ring.into_iter().eq(model));
+});
@@ -0,0 +1,39 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lists::shared_list::List;
+use lists_fuzz::Op;
+
+// Besides matching the model, every version the script passes through must
+// survive unchanged: later appends and tails share nodes with it.
+fuzz_target!(|ops: Vec<Op>| {
+    let mut list = List::new();
+    // Front to back, so it is reversed relative to pushes.
+    let mut model: Vec<u16> = Vec::new();
+    let mut versions = Vec::new();
+
+    for op in ops {
+        match op {
+            Op::PushFront(elem) => {
+                list = list.append(elem);
+                model.insert(0, elem);
+            }
+            Op::PopFront => {
+                assert_eq!(list.head(), model.first());
+                list = list.tail();
+                if !model.is_empty() {
+                    model.remove(0);
+                }
+            }
+            Op::PeekFront => assert_eq!(list.head(), model.first()),
+            Op::Iter => assert!(list.iter().eq(model.iter())),
+            Op::Clone => versions.push((list.clone(), model.clone())),
+            _ => {}
+        }
+    }
+
+    for (version, expected) in versions {
+        assert!(version.iter().eq(expected.iter()));
+    }
+    assert!(list.iter().eq(model.iter()));
+});
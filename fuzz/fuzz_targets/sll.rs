@@ -0,0 +1,144 @@
+#![no_main]
+
+use std::collections::VecDeque;
+
+use libfuzzer_sys::fuzz_target;
+use lists::allocator::{Allocator, Global};
+use lists::sll::List;
+use lists_fuzz::{Budget, Op};
+
+// The script runs once on Global, where lists can be appended to each other,
+// and once on a Budget, which can turn down try_push.
+trait Alloc: Allocator + Clone {
+    // Whether lists on this allocator can be appended to each other.
+    const APPENDS: bool;
+    fn append(list: &mut List<u16, Self>, other: &mut List<u16, Self>);
+    // Leaves room for one more node, or none, and says whether a push will
+    // get one.
+    fn room(&self, room: bool) -> bool;
+    fn reset(&self) {}
+}
+
+impl Alloc for Global {
+    const APPENDS: bool = true;
+
+    fn append(list: &mut List<u16, Self>, other: &mut List<u16, Self>) {
+        list.append(other);
+    }
+
+    // Never fails.
+    fn room(&self, _: bool) -> bool {
+        true
+    }
+}
+
+impl Alloc for Budget {
+    const APPENDS: bool = false;
+
+    fn append(_: &mut List<u16, Self>, _: &mut List<u16, Self>) {
+        unreachable!()
+    }
+
+    fn room(&self, room: bool) -> bool {
+        self.set(usize::from(room));
+        room
+    }
+
+    fn reset(&self) {
+        self.set(usize::MAX);
+    }
+}
+
+fn list_of<A: Alloc>(elems: &[u16], alloc: &A) -> List<u16, A> {
+    let mut list = List::new_in(alloc.clone());
+    for &elem in elems.iter().rev() {
+        list.push(elem);
+    }
+    list
+}
+
+fn run<A: Alloc>(ops: &[Op], alloc: A) {
+    let mut list = List::new_in(alloc.clone());
+    let mut model = VecDeque::new();
+
+    for op in ops {
+        match op {
+            &Op::PushFront(elem) => {
+                list.push(elem);
+                model.push_front(elem);
+            }
+            &Op::TryPushFront(elem, room) => {
+                let fits = alloc.room(room);
+                let res = list.try_push(elem);
+                alloc.reset();
+                assert_eq!(res.is_ok(), fits);
+                match res {
+                    Ok(()) => model.push_front(elem),
+                    Err((back, _)) => assert_eq!(back, elem),
+                }
+            }
+            Op::PopFront => assert_eq!(list.pop(), model.pop_front()),
+            Op::PeekFront => assert_eq!(list.peek(), model.front()),
+            &Op::PeekFrontMut(elem) => match (list.peek_mut(), model.front_mut()) {
+                (Some(got), Some(want)) => {
+                    *got = elem;
+                    *want = elem;
+                }
+                (got, want) => assert!(got.is_none() && want.is_none()),
+            },
+            Op::Iter => {
+                assert!(list.iter().eq(model.iter()));
+                assert!(list.iter_alt().eq(model.iter()));
+            }
+            &Op::IterMut(delta) => {
+                for elem in list.iter_mut() {
+                    *elem = elem.wrapping_add(delta);
+                }
+                for elem in model.iter_mut() {
+                    *elem = elem.wrapping_add(delta);
+                }
+            }
+            Op::Clone => {
+                let copy = list.clone();
+                assert!(copy.iter().eq(list.iter()));
+                list = copy;
+            }
+            &Op::SplitOff(at) => {
+                let at = usize::from(at) % (model.len() + 1);
+                let rest = list.split_off(at);
+                assert!(rest.into_iter().eq(model.split_off(at)));
+            }
+            Op::Append(elems) if A::APPENDS => {
+                let mut other = list_of(elems, &alloc);
+                A::append(&mut list, &mut other);
+                assert!(other.peek().is_none());
+                model.extend(elems);
+            }
+            Op::Splice(at, elems) if A::APPENDS => {
+                let at = usize::from(*at) % (model.len() + 1);
+                let mut rest = list.split_off(at);
+                A::append(&mut list, &mut list_of(elems, &alloc));
+                A::append(&mut list, &mut rest);
+                for (i, &elem) in elems.iter().enumerate() {
+                    model.insert(at + i, elem);
+                }
+            }
+            &Op::Retain(n) => {
+                list.retain(|&elem| Op::keeps(n, elem));
+                model.retain(|&elem| Op::keeps(n, elem));
+            }
+            Op::Sort => {
+                list.sort_by(|a, b| a.cmp(b));
+                model.make_contiguous().sort();
+            }
+            _ => {}
+        }
+    }
+
+    assert!(list.into_iter().eq(model));
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    run(&ops, Global);
+    run(&ops, Budget::new(usize::MAX));
+});
@@ -0,0 +1,114 @@
+#![no_main]
+
+use std::collections::VecDeque;
+
+use libfuzzer_sys::fuzz_target;
+use lists::static_list::{StaticDeque, StaticList};
+use lists_fuzz::Op;
+
+const CAP: usize = 8;
+
+fn run_list(ops: &[Op]) {
+    let mut list = StaticList::<u16, CAP>::new();
+    let mut model = VecDeque::new();
+
+    for op in ops {
+        match *op {
+            Op::PushFront(elem) => match list.push(elem) {
+                Ok(()) => model.push_front(elem),
+                Err(back) => {
+                    assert_eq!(back, elem);
+                    assert_eq!(model.len(), CAP);
+                }
+            },
+            Op::PopFront => assert_eq!(list.pop(), model.pop_front()),
+            Op::PeekFront => assert_eq!(list.peek(), model.front()),
+            Op::PeekFrontMut(elem) => {
+                if let Some(got) = list.peek_mut() {
+                    *got = elem;
+                    *model.front_mut().unwrap() = elem;
+                }
+            }
+            Op::Iter => assert!(list.iter().eq(model.iter())),
+            Op::IterMut(delta) => {
+                for elem in list.iter_mut() {
+                    *elem = elem.wrapping_add(delta);
+                }
+                for elem in model.iter_mut() {
+                    *elem = elem.wrapping_add(delta);
+                }
+            }
+            _ => {}
+        }
+        assert_eq!(list.len(), model.len());
+    }
+
+    assert!(list.into_iter().eq(model));
+}
+
+fn run_deque(ops: &[Op]) {
+    let mut list = StaticDeque::<u16, CAP>::new();
+    let mut model = VecDeque::new();
+
+    for op in ops {
+        match *op {
+            Op::PushFront(elem) | Op::PushBack(elem) => {
+                let front = matches!(op, Op::PushFront(_));
+                let res = if front {
+                    list.push_front(elem)
+                } else {
+                    list.push_back(elem)
+                };
+                match res {
+                    Ok(()) if front => model.push_front(elem),
+                    Ok(()) => model.push_back(elem),
+                    Err(back) => {
+                        assert_eq!(back, elem);
+                        assert_eq!(model.len(), CAP);
+                    }
+                }
+            }
+            Op::PopFront => assert_eq!(list.pop_front(), model.pop_front()),
+            Op::PopBack => assert_eq!(list.pop_back(), model.pop_back()),
+            Op::PeekFront => assert_eq!(list.peek_front(), model.front()),
+            Op::PeekBack => assert_eq!(list.peek_back(), model.back()),
+            Op::PeekFrontMut(elem) => {
+                if let Some(got) = list.peek_front_mut() {
+                    *got = elem;
+                    *model.front_mut().unwrap() = elem;
+                }
+            }
+            Op::PeekBackMut(elem) => {
+                if let Some(got) = list.peek_back_mut() {
+                    *got = elem;
+                    *model.back_mut().unwrap() = elem;
+                }
+            }
+            Op::Iter => {
+                assert!(list.iter().eq(model.iter()));
+                assert!(list.iter().rev().eq(model.iter().rev()));
+            }
+            Op::IterMut(delta) => {
+                for elem in list.iter_mut() {
+                    *elem = elem.wrapping_add(delta);
+                }
+                for elem in model.iter_mut() {
+                    *elem = elem.wrapping_add(delta);
+                }
+            }
+            _ => {}
+        }
+        assert_eq!(list.len(), model.len());
+    }
+
+    let mut iter = list.into_iter();
+    while let Some(want) = model.pop_back() {
+        assert_eq!(iter.next_back(), Some(want));
+    }
+    assert_eq!(iter.next(), None);
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    run_list(&ops);
+    run_deque(&ops);
+});
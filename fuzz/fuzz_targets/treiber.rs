@@ -0,0 +1,39 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use lists::treiber::Stack;
+use lists_fuzz::{all_once, race, Op};
+
+fuzz_target!(|ops: Vec<Op>| {
+    // One thread, against a Vec.
+    let stack = Stack::new();
+    let mut model = Vec::new();
+    for op in &ops {
+        match *op {
+            Op::PushFront(elem) | Op::PushBack(elem) => {
+                stack.push(elem);
+                model.push(elem);
+            }
+            Op::PopFront | Op::PopBack => assert_eq!(stack.pop(), model.pop()),
+            _ => {}
+        }
+        assert_eq!(stack.is_empty(), model.is_empty());
+    }
+    // Drop whatever is left with the stack half the time.
+    if ops.len() % 2 == 0 {
+        while let Some(elem) = stack.pop() {
+            assert_eq!(Some(elem), model.pop());
+        }
+        assert!(model.is_empty());
+    }
+    drop(stack);
+
+    // Several threads at once: nothing lost, nothing popped twice.
+    let stack = Stack::new();
+    let (popped, pushes) = race(&ops, &stack, Stack::push, Stack::pop);
+    let mut out: Vec<_> = popped.into_iter().flatten().collect();
+    while let Some(elem) = stack.pop() {
+        out.push(elem);
+    }
+    all_once(out, pushes);
+});
@@ -0,0 +1,138 @@
+#![no_main]
+
+use std::collections::VecDeque;
+
+use libfuzzer_sys::fuzz_target;
+use lists::xor_list::XorList;
+use lists_fuzz::{Budget, Op};
+
+fuzz_target!(|ops: Vec<Op>| {
+    let alloc = Budget::new(usize::MAX);
+    let mut list = XorList::new_in(alloc.clone());
+    let mut model = VecDeque::new();
+    // reverse() flips the list in O(1); the model keeps the original order
+    // and reads it backwards while this is set.
+    let mut reversed = false;
+
+    for op in ops {
+        // What the op means on the model, which may be the other end.
+        let front = |front: bool| front != reversed;
+        match op {
+            Op::PushFront(elem) | Op::PushBack(elem) => {
+                if matches!(op, Op::PushFront(_)) {
+                    list.push_front(elem);
+                } else {
+                    list.push_back(elem);
+                }
+                match front(matches!(op, Op::PushFront(_))) {
+                    true => model.push_front(elem),
+                    false => model.push_back(elem),
+                }
+            }
+            Op::TryPushFront(elem, room) | Op::TryPushBack(elem, room) => {
+                let at_front = matches!(op, Op::TryPushFront(..));
+                alloc.set(usize::from(room));
+                let res = if at_front {
+                    list.try_push_front(elem)
+                } else {
+                    list.try_push_back(elem)
+                };
+                alloc.set(usize::MAX);
+                assert_eq!(res.is_ok(), room);
+                match res {
+                    Ok(()) if front(at_front) => model.push_front(elem),
+                    Ok(()) => model.push_back(elem),
+                    Err((back, _)) => assert_eq!(back, elem),
+                }
+            }
+            Op::PopFront | Op::TryPopFront => {
+                let want = if front(true) { model.pop_front() } else { model.pop_back() };
+                assert_eq!(list.pop_front(), want);
+            }
+            Op::PopBack | Op::TryPopBack => {
+                let want = if front(false) { model.pop_front() } else { model.pop_back() };
+                assert_eq!(list.pop_back(), want);
+            }
+            Op::PeekFront => {
+                let want = if front(true) { model.front() } else { model.back() };
+                assert_eq!(list.peek_front(), want);
+            }
+            Op::PeekBack => {
+                let want = if front(false) { model.front() } else { model.back() };
+                assert_eq!(list.peek_back(), want);
+            }
+            Op::PeekFrontMut(elem) | Op::PeekBackMut(elem) => {
+                let at_front = matches!(op, Op::PeekFrontMut(_));
+                let got = if at_front {
+                    list.peek_front_mut()
+                } else {
+                    list.peek_back_mut()
+                };
+                let want = if front(at_front) {
+                    model.front_mut()
+                } else {
+                    model.back_mut()
+                };
+                match (got, want) {
+                    (Some(got), Some(want)) => {
+                        *got = elem;
+                        *want = elem;
+                    }
+                    (got, want) => assert!(got.is_none() && want.is_none()),
+                }
+            }
+            Op::Iter => {
+                if reversed {
+                    assert!(list.iter().eq(model.iter().rev()));
+                    assert!(list.iter().rev().eq(model.iter()));
+                } else {
+                    assert!(list.iter().eq(model.iter()));
+                    assert!(list.iter().rev().eq(model.iter().rev()));
+                }
+                assert_eq!(list.len(), model.len());
+            }
+            Op::IterMut(delta) => {
+                for elem in list.iter_mut() {
+                    *elem = elem.wrapping_add(delta);
+                }
+                for elem in model.iter_mut() {
+                    *elem = elem.wrapping_add(delta);
+                }
+            }
+            Op::Clone => {
+                let copy = list.clone();
+                assert!(copy.iter().eq(list.iter()));
+                list = copy;
+            }
+            Op::Reverse => {
+                list.reverse();
+                reversed = !reversed;
+            }
+            Op::Sort => {
+                list.sort_by(|a, b| a.cmp(b));
+                model.make_contiguous().sort();
+                if reversed {
+                    model.make_contiguous().reverse();
+                }
+            }
+            _ => {}
+        }
+        assert_eq!(list.len(), model.len());
+    }
+
+    if reversed {
+        model.make_contiguous().reverse();
+    }
+    // Drain from both ends so both unlink paths run.
+    let mut iter = list.into_iter();
+    loop {
+        let (got, want) = match model.len() % 2 {
+            0 => (iter.next(), model.pop_front()),
+            _ => (iter.next_back(), model.pop_back()),
+        };
+        assert_eq!(got, want);
+        if got.is_none() {
+            break;
+        }
+    }
+});
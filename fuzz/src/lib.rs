@@ -0,0 +1,153 @@
+// Shared pieces of the fuzz targets. Each target turns the fuzzer's bytes
+// into a script of `Op`s (via `Arbitrary`), runs it against one list type and
+// a VecDeque model, and asserts they agree, so any divergence, panic or
+// memory error becomes a crash. Ops a list doesn't support are skipped.
+//
+// The lock-free targets (treiber, ms_queue) also replay the script from two
+// threads at once and check that no element is lost or duplicated.
+//
+// Run with (nightly):
+//   cargo fuzz run deque_book
+//   cargo fuzz run sll -- -max_total_time=60
+
+use std::alloc::Layout;
+use std::cell::Cell;
+use std::ptr::NonNull;
+use std::rc::Rc;
+
+use arbitrary::Arbitrary;
+use lists::allocator::{AllocError, Allocator, Global};
+
+#[derive(Arbitrary, Debug, Clone)]
+pub enum Op {
+    PushFront(u16),
+    PushBack(u16),
+    // The try_push* paths; the flag says whether the allocator has room for
+    // another node.
+    TryPushFront(u16, bool),
+    TryPushBack(u16, bool),
+    PopFront,
+    PopBack,
+    TryPopFront,
+    TryPopBack,
+    PeekFront,
+    PeekBack,
+    // Overwrite the element at that end.
+    PeekFrontMut(u16),
+    PeekBackMut(u16),
+    // Walk the whole list and compare against the model.
+    Iter,
+    IterMut(u16),
+    Clone,
+    // Reduced modulo len + 1.
+    SplitOff(u16),
+    Append(Vec<u16>),
+    // Insert the elements at that position (reduced modulo len + 1) by
+    // cutting the list there and splicing them in.
+    Splice(u16, Vec<u16>),
+    // Keep the elements that aren't multiples of n % 7 + 2.
+    Retain(u8),
+    Sort,
+    Reverse,
+    ShrinkPool,
+    // Ring's cursor: forward that many steps, back one, and the start of
+    // the span that split cuts out.
+    Advance(u8),
+    Retreat,
+    SetMark,
+    // deque_book: leak a borrow of one end of a copy of the list (how % 2:
+    // front or back; how / 2 % 3: peek, peek_mut or an iterator's first
+    // item) and check what still works. The u16 is a split point, reduced
+    // modulo len + 1, and also the element appended.
+    Stuck(u8, u16),
+}
+
+impl Op {
+    // The predicate for Retain(n).
+    pub fn keeps(n: u8, elem: u16) -> bool {
+        elem % (u16::from(n % 7) + 2) != 0
+    }
+}
+
+// Global, but refuses to allocate once its budget runs out, so the try_push*
+// failure paths get exercised. Clones share the budget.
+#[derive(Clone)]
+pub struct Budget(Rc<Cell<usize>>);
+
+impl Budget {
+    pub fn new(budget: usize) -> Self {
+        Budget(Rc::new(Cell::new(budget)))
+    }
+
+    pub fn set(&self, budget: usize) {
+        self.0.set(budget);
+    }
+}
+
+unsafe impl Allocator for Budget {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        match self.0.get() {
+            0 => Err(AllocError),
+            n => {
+                self.0.set(n - 1);
+                Global.allocate(layout)
+            }
+        }
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        Global.deallocate(ptr, layout)
+    }
+}
+
+// An element pushed during `race`: which thread pushed it, and how many that
+// thread had pushed before.
+pub type Tagged = (usize, usize);
+
+pub const THREADS: usize = 2;
+
+// Replays `ops` from THREADS threads at once against one shared structure:
+// PushFront and PushBack push the next Tagged, PopFront and PopBack pop.
+// Returns what each thread popped, in order, and how many each pushed.
+pub fn race<S: Sync>(
+    ops: &[Op],
+    shared: &S,
+    push: impl Fn(&S, Tagged) + Sync,
+    pop: impl Fn(&S) -> Option<Tagged> + Sync,
+) -> (Vec<Vec<Tagged>>, usize) {
+    let pushes = ops
+        .iter()
+        .filter(|op| matches!(op, Op::PushFront(_) | Op::PushBack(_)))
+        .count();
+    let (push, pop) = (&push, &pop);
+    let popped = std::thread::scope(|s| {
+        let threads: Vec<_> = (0..THREADS)
+            .map(|thread| {
+                s.spawn(move || {
+                    let (mut pushed, mut popped) = (0, Vec::new());
+                    for op in ops {
+                        match op {
+                            Op::PushFront(_) | Op::PushBack(_) => {
+                                push(shared, (thread, pushed));
+                                pushed += 1;
+                            }
+                            Op::PopFront | Op::PopBack => popped.extend(pop(shared)),
+                            _ => {}
+                        }
+                    }
+                    popped
+                })
+            })
+            .collect();
+        threads.into_iter().map(|thread| thread.join().unwrap()).collect()
+    });
+    (popped, pushes)
+}
+
+// Checks that `out`, everything that left the structure, is exactly what
+// `race` put in: each element once.
+pub fn all_once(mut out: Vec<Tagged>, pushes: usize) {
+    out.sort_unstable();
+    let pushed = (0..THREADS).flat_map(|thread| (0..pushes).map(move |n| (thread, n)));
+    assert!(out.into_iter().eq(pushed));
+}
@@ -0,0 +1,177 @@
+use alloc::alloc as heap;
+use core::alloc::Layout;
+use core::fmt;
+use core::ptr::NonNull;
+
+/// A stable stand-in for the unstable `std::alloc::Allocator` trait. Lists that
+/// take an allocator parameter get all of their node memory from it, and
+/// default to `Global` so that existing code keeps using the system heap.
+///
+/// # Safety
+///
+/// Implementors must hand out blocks that are valid for `layout` and stay
+/// valid until passed back to `deallocate` on the same allocator (or a clone
+/// of it).
+pub unsafe trait Allocator {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError>;
+
+    /// # Safety
+    ///
+    /// `ptr` must have come from `allocate` with this same `layout`.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Global;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AllocError {}
+
+unsafe impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() == 0 {
+            // SAFETY: align is never zero.
+            return Ok(unsafe { NonNull::new_unchecked(layout.align() as *mut u8) });
+        }
+        NonNull::new(unsafe { heap::alloc(layout) }).ok_or(AllocError)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            heap::dealloc(ptr.as_ptr(), layout)
+        }
+    }
+}
+
+unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+}
+
+// Moves `value` into memory from `alloc`, handing it back if allocation fails.
+pub(crate) fn try_alloc_in<T, A: Allocator>(
+    value: T,
+    alloc: &A,
+) -> Result<NonNull<T>, (T, AllocError)> {
+    match alloc.allocate(Layout::new::<T>()) {
+        Ok(ptr) => {
+            let ptr = ptr.cast::<T>();
+            unsafe { ptr.as_ptr().write(value) };
+            Ok(ptr)
+        }
+        Err(err) => Err((value, err)),
+    }
+}
+
+pub(crate) fn alloc_in<T, A: Allocator>(value: T, alloc: &A) -> NonNull<T> {
+    match try_alloc_in(value, alloc) {
+        Ok(ptr) => ptr,
+        Err(_) => heap::handle_alloc_error(Layout::new::<T>()),
+    }
+}
+
+// Moves the value back out of `ptr` and returns the memory to `alloc`.
+//
+// SAFETY: `ptr` must come from `alloc_in`/`try_alloc_in` on the same allocator
+// and must not be used again.
+pub(crate) unsafe fn take_in<T, A: Allocator>(ptr: NonNull<T>, alloc: &A) -> T {
+    let value = ptr.as_ptr().read();
+    alloc.deallocate(ptr.cast(), Layout::new::<T>());
+    value
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use core::cell::Cell;
+    use std::string::String;
+
+    // Forwards to Global and keeps score, so tests can check that a list
+    // returns every node it allocated, and how big its nodes are.
+    #[derive(Default)]
+    pub(crate) struct Counting {
+        pub(crate) allocs: Cell<usize>,
+        pub(crate) deallocs: Cell<usize>,
+        // Bytes currently allocated.
+        pub(crate) bytes: Cell<usize>,
+    }
+
+    impl Counting {
+        pub(crate) fn live(&self) -> usize {
+            self.allocs.get() - self.deallocs.get()
+        }
+    }
+
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+            self.allocs.set(self.allocs.get() + 1);
+            self.bytes.set(self.bytes.get() + layout.size());
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.deallocs.set(self.deallocs.get() + 1);
+            self.bytes.set(self.bytes.get() - layout.size());
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    // Allows at most `budget` live allocations and fails the rest, to
+    // simulate memory pressure.
+    pub(crate) struct Failing {
+        budget: usize,
+        inner: Counting,
+    }
+
+    impl Failing {
+        pub(crate) fn new(budget: usize) -> Self {
+            Failing {
+                budget,
+                inner: Counting::default(),
+            }
+        }
+
+        pub(crate) fn live(&self) -> usize {
+            self.inner.live()
+        }
+    }
+
+    unsafe impl Allocator for Failing {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+            if self.inner.live() >= self.budget {
+                return Err(AllocError);
+            }
+            self.inner.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.inner.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let counting = Counting::default();
+        let ptr = alloc_in(String::from("hello"), &&counting);
+        assert_eq!(counting.live(), 1);
+        let s = unsafe { take_in(ptr, &&counting) };
+        assert_eq!(s, "hello");
+        assert_eq!(counting.live(), 0);
+        assert_eq!(counting.allocs.get(), 1);
+        assert_eq!(counting.bytes.get(), 0);
+    }
+}
@@ -0,0 +1,346 @@
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use futures_core::Stream;
+use futures_sink::Sink;
+
+pub use crate::channel::SendError;
+use crate::ms_queue::Queue;
+
+// An unbounded async queue. Senders push straight into an ms_queue::Queue and
+// wake the receiver, which implements Stream. The stream ends once the queue
+// has been drained and either every sender is gone or the receiver closed it.
+pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        queue: Queue::new(),
+        waker: Mutex::new(None),
+        senders: AtomicUsize::new(1),
+        closed: AtomicBool::new(false),
+        sending: AtomicUsize::new(0),
+    });
+    (
+        Sender {
+            shared: Some(shared.clone()),
+        },
+        Receiver { shared },
+    )
+}
+
+struct Shared<T> {
+    queue: Queue<T>,
+    waker: Mutex<Option<Waker>>,
+    senders: AtomicUsize,
+    // Set when the receiver closes or goes away; no more sends are accepted.
+    closed: AtomicBool,
+    // Sends that got past the closed check but may not have pushed yet. The
+    // stream can't end while there are any.
+    sending: AtomicUsize,
+}
+
+impl<T> Shared<T> {
+    fn wake(&self) {
+        let waker = self.waker.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/********** Sender **********/
+
+pub struct Sender<T> {
+    // None once this sender has been closed through Sink::poll_close.
+    shared: Option<Arc<Shared<T>>>,
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, elem: T) -> Result<(), SendError<T>> {
+        match &self.shared {
+            Some(shared) => {
+                shared.sending.fetch_add(1, SeqCst);
+                if shared.closed.load(SeqCst) {
+                    shared.sending.fetch_sub(1, SeqCst);
+                    return Err(SendError(elem));
+                }
+                shared.queue.push(elem);
+                shared.sending.fetch_sub(1, SeqCst);
+                shared.wake();
+                Ok(())
+            }
+            None => Err(SendError(elem)),
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        match &self.shared {
+            Some(shared) => shared.closed.load(SeqCst),
+            None => true,
+        }
+    }
+
+    // Drops this sender's hold on the channel. The receiver sees the end of
+    // the stream once every sender has disconnected.
+    pub fn disconnect(&mut self) {
+        if let Some(shared) = self.shared.take() {
+            if shared.senders.fetch_sub(1, SeqCst) == 1 {
+                shared.wake();
+            }
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        if let Some(shared) = &self.shared {
+            shared.senders.fetch_add(1, SeqCst);
+        }
+        Sender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.disconnect();
+    }
+}
+
+impl<T> Sink<T> for Sender<T> {
+    type Error = SendError<()>;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.is_closed() {
+            Poll::Ready(Err(SendError(())))
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, elem: T) -> Result<(), Self::Error> {
+        self.send(elem).map_err(|_| SendError(()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.disconnect();
+        Poll::Ready(Ok(()))
+    }
+}
+
+/********** Receiver **********/
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Receiver<T> {
+    pub fn try_recv(&self) -> Option<T> {
+        self.shared.queue.try_pop()
+    }
+
+    // Stops accepting new elements. Whatever was already sent can still be
+    // received.
+    pub fn close(&self) {
+        self.shared.closed.store(true, SeqCst);
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let shared = &*self.shared;
+        if let Some(elem) = shared.queue.try_pop() {
+            return Poll::Ready(Some(elem));
+        }
+
+        *shared.waker.lock().unwrap_or_else(|e| e.into_inner()) = Some(cx.waker().clone());
+
+        // Look again now that the waker is in place, otherwise a push that
+        // landed in between would go unnoticed.
+        if let Some(elem) = shared.queue.try_pop() {
+            return Poll::Ready(Some(elem));
+        }
+        let closed = shared.closed.load(SeqCst) && shared.sending.load(SeqCst) == 0;
+        if closed || shared.senders.load(SeqCst) == 0 {
+            // The last sender may have pushed right before leaving, or a send
+            // that beat close() right before finishing.
+            return Poll::Ready(shared.queue.try_pop());
+        }
+        Poll::Pending
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(all(test, not(feature = "loom")))]
+mod test {
+    use super::*;
+    use std::future::{poll_fn, Future};
+    use std::pin::pin;
+    use std::sync::Arc;
+    use std::task::Wake;
+    use std::thread::{self, Thread};
+    use std::time::Duration;
+    use std::vec::Vec;
+
+    // Just enough of an executor to drive a single future to completion on
+    // the current thread.
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut fut = pin!(fut);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+                return out;
+            }
+            thread::park();
+        }
+    }
+
+    async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+        poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    async fn sink_send<S: Sink<T> + Unpin, T>(sink: &mut S, elem: T) -> Result<(), S::Error> {
+        poll_fn(|cx| Pin::new(&mut *sink).poll_ready(cx)).await?;
+        Pin::new(&mut *sink).start_send(elem)?;
+        poll_fn(|cx| Pin::new(&mut *sink).poll_flush(cx)).await
+    }
+
+    async fn sink_close<S: Sink<T> + Unpin, T>(sink: &mut S) -> Result<(), S::Error> {
+        poll_fn(|cx| Pin::new(&mut *sink).poll_close(cx)).await
+    }
+
+    #[test]
+    fn basics() {
+        let (tx, mut rx) = unbounded();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(block_on(next(&mut rx)), Some(1));
+        assert_eq!(rx.try_recv(), Some(2));
+        assert_eq!(rx.try_recv(), None);
+        drop(tx);
+        assert_eq!(block_on(next(&mut rx)), None);
+    }
+
+    #[test]
+    fn wakes_across_threads() {
+        let (tx, mut rx) = unbounded();
+        let producers: Vec<_> = (0..4)
+            .map(|p| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..1000 {
+                        tx.send(p * 1000 + i).unwrap();
+                        if i % 100 == 0 {
+                            thread::sleep(Duration::from_millis(1));
+                        }
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut got = block_on(async {
+            let mut got = vec![];
+            while let Some(n) = next(&mut rx).await {
+                got.push(n);
+            }
+            got
+        });
+        got.sort();
+        assert_eq!(got, (0..4000).collect::<Vec<_>>());
+        for p in producers {
+            p.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn sink() {
+        let (mut tx, mut rx) = unbounded();
+        let mut tx2 = tx.clone();
+        block_on(async {
+            sink_send(&mut tx, 1).await.unwrap();
+            sink_send(&mut tx2, 2).await.unwrap();
+            sink_close(&mut tx).await.unwrap();
+            assert_eq!(sink_send(&mut tx, 3).await, Err(SendError(())));
+            assert_eq!(next(&mut rx).await, Some(1));
+            assert_eq!(next(&mut rx).await, Some(2));
+
+            sink_close(&mut tx2).await.unwrap();
+            assert_eq!(next(&mut rx).await, None);
+        });
+    }
+
+    #[test]
+    fn receiver_close() {
+        let (tx, mut rx) = unbounded();
+        tx.send(1).unwrap();
+        rx.close();
+        assert!(tx.is_closed());
+        assert_eq!(tx.send(2), Err(SendError(2)));
+        assert_eq!(block_on(next(&mut rx)), Some(1));
+        drop(tx);
+        assert_eq!(block_on(next(&mut rx)), None);
+
+        let (tx, rx) = unbounded::<i32>();
+        drop(rx);
+        assert_eq!(tx.send(1), Err(SendError(1)));
+    }
+
+    // Closing ends the stream once it's drained, even with senders still
+    // around.
+    #[test]
+    fn receiver_close_with_live_senders() {
+        let (tx, mut rx) = unbounded();
+        let tx2 = tx.clone();
+        tx.send(1).unwrap();
+        rx.close();
+        assert_eq!(block_on(next(&mut rx)), Some(1));
+        assert_eq!(block_on(next(&mut rx)), None);
+        assert_eq!(block_on(next(&mut rx)), None);
+        assert_eq!(tx2.send(2), Err(SendError(2)));
+
+
+        // Every send that was accepted before the close is still received,
+        // however it raced with it.
+        let (tx, mut rx) = unbounded();
+        let senders: Vec<_> = (0..4)
+            .map(|_| {
+                let tx = tx.clone();
+                thread::spawn(move || (0..).take_while(|&i| tx.send(i).is_ok()).count())
+            })
+            .collect();
+        thread::sleep(Duration::from_millis(5));
+        rx.close();
+        let got = block_on(async {
+            let mut got = 0;
+            while next(&mut rx).await.is_some() {
+                got += 1;
+            }
+            got
+        });
+        let sent: usize = senders.into_iter().map(|s| s.join().unwrap()).sum();
+        assert_eq!(got, sent);
+        assert!(tx.is_closed());
+    }
+}
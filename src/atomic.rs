@@ -0,0 +1,9 @@
+// Atomics used by the concurrent lists. With the `loom` feature these are
+// swapped for loom's model-checked versions so the same code can be explored
+// under `loom::model`.
+
+#[cfg(feature = "loom")]
+pub(crate) use loom::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+#[cfg(not(feature = "loom"))]
+pub(crate) use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
@@ -0,0 +1,604 @@
+use std::error;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::ms_queue::Queue;
+
+// A bounded multi-producer single-consumer channel with the same surface as
+// std::sync::mpsc::sync_channel. Elements travel through an ms_queue::Queue;
+// the mutex only guards the bookkeeping needed to block and wake up.
+//
+// Blocked senders are served strictly in arrival order: each one takes a
+// ticket and only the sender holding `serving` may enqueue. A bound of 0
+// makes a rendezvous channel, where a send completes only by handing its
+// value to a receiver: straight through the queue if one is already
+// waiting, otherwise by holding it out until a recv or try_recv takes it.
+pub fn sync_channel<T>(bound: usize) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        queue: Queue::new(),
+        bound,
+        state: Mutex::new(State {
+            len: 0,
+            senders: 1,
+            receiver: true,
+            waiting_receivers: 0,
+            next_ticket: 0,
+            serving: 0,
+            offer: None,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+    });
+    (
+        Sender {
+            shared: shared.clone(),
+        },
+        Receiver { shared },
+    )
+}
+
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+struct Shared<T> {
+    queue: Queue<T>,
+    bound: usize,
+    state: Mutex<State<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+
+struct State<T> {
+    len: usize,
+    senders: usize,
+    receiver: bool,
+    waiting_receivers: usize,
+    next_ticket: u64,
+    serving: u64,
+    // The element a rendezvous sender is blocked handing over, if any.
+    offer: Option<T>,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn has_room(&self, state: &State<T>) -> bool {
+        if self.bound == 0 {
+            state.len == 0 && state.waiting_receivers > 0
+        } else {
+            state.len < self.bound
+        }
+    }
+
+    // Called with the lock held and room available.
+    fn enqueue(&self, state: &mut State<T>, elem: T) {
+        state.len += 1;
+        self.queue.push(elem);
+        self.not_empty.notify_one();
+    }
+
+    // Called with the lock held and at least one element queued.
+    fn dequeue(&self, state: &mut State<T>) -> T {
+        state.len -= 1;
+        if state.next_ticket != state.serving {
+            // Senders are waiting for their turn; wake them all so the one
+            // holding the current ticket can go.
+            self.not_full.notify_all();
+        }
+        self.queue.try_pop().expect("len counts queued elements")
+    }
+
+    // Called with the lock held. The next element, queued or offered.
+    fn take(&self, state: &mut State<T>) -> Option<T> {
+        if state.len > 0 {
+            return Some(self.dequeue(state));
+        }
+        let elem = state.offer.take()?;
+        self.not_full.notify_all();
+        Some(elem)
+    }
+}
+
+/********** Errors **********/
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct SendError<T>(pub T);
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum TrySendError<T> {
+    Full(T),
+    Disconnected(T),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct RecvError;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvTimeoutError {
+    Timeout,
+    Disconnected,
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("sending on a closed channel")
+    }
+}
+
+impl<T> error::Error for SendError<T> {}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("Full(..)"),
+            TrySendError::Disconnected(_) => f.write_str("Disconnected(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => f.write_str("sending on a full channel"),
+            TrySendError::Disconnected(_) => f.write_str("sending on a closed channel"),
+        }
+    }
+}
+
+impl<T> error::Error for TrySendError<T> {}
+
+impl<T> From<SendError<T>> for TrySendError<T> {
+    fn from(err: SendError<T>) -> Self {
+        TrySendError::Disconnected(err.0)
+    }
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("receiving on a closed channel")
+    }
+}
+
+impl error::Error for RecvError {}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => f.write_str("receiving on an empty channel"),
+            TryRecvError::Disconnected => f.write_str("receiving on a closed channel"),
+        }
+    }
+}
+
+impl error::Error for TryRecvError {}
+
+impl fmt::Display for RecvTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout => f.write_str("timed out waiting on channel"),
+            RecvTimeoutError::Disconnected => f.write_str("channel is empty and sending half is closed"),
+        }
+    }
+}
+
+impl error::Error for RecvTimeoutError {}
+
+/********** Sender **********/
+
+impl<T> Sender<T> {
+    pub fn send(&self, elem: T) -> Result<(), SendError<T>> {
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+
+        // A rendezvous sender goes as soon as it's its turn.
+        while state.receiver
+            && !(ticket == state.serving && (shared.bound == 0 || shared.has_room(&state)))
+        {
+            state = shared.not_full.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        let result = if !state.receiver {
+            Err(SendError(elem))
+        } else if shared.has_room(&state) {
+            shared.enqueue(&mut state, elem);
+            Ok(())
+        } else {
+            // No receiver waiting yet: hold the element out for the next
+            // recv or try_recv, and take it back if the receiver goes.
+            state.offer = Some(elem);
+            while state.receiver && state.offer.is_some() {
+                state = shared.not_full.wait(state).unwrap_or_else(|e| e.into_inner());
+            }
+            state.offer.take().map_or(Ok(()), |elem| Err(SendError(elem)))
+        };
+        state.serving += 1;
+        if state.next_ticket != state.serving {
+            shared.not_full.notify_all();
+        }
+        result
+    }
+
+    pub fn try_send(&self, elem: T) -> Result<(), TrySendError<T>> {
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+        if !state.receiver {
+            return Err(TrySendError::Disconnected(elem));
+        }
+        // Don't jump ahead of senders that are already blocked.
+        if state.next_ticket != state.serving || !shared.has_room(&state) {
+            return Err(TrySendError::Full(elem));
+        }
+        shared.enqueue(&mut state, elem);
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.lock().senders += 1;
+        Sender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.lock();
+        state.senders -= 1;
+        if state.senders == 0 {
+            self.shared.not_empty.notify_all();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sender { .. }")
+    }
+}
+
+/********** Receiver **********/
+
+impl<T> Receiver<T> {
+    pub fn recv(&self) -> Result<T, RecvError> {
+        self.recv_until(None).map_err(|_| RecvError)
+    }
+
+    pub fn try_recv(&self) -> Result<T, TryRecvError> {
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+        if let Some(elem) = shared.take(&mut state) {
+            Ok(elem)
+        } else if state.senders == 0 {
+            Err(TryRecvError::Disconnected)
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T, RecvTimeoutError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.recv_until(Some(deadline)),
+            None => self.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        }
+    }
+
+    fn recv_until(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+        loop {
+            if let Some(elem) = shared.take(&mut state) {
+                return Ok(elem);
+            }
+            if state.senders == 0 {
+                return Err(RecvTimeoutError::Disconnected);
+            }
+
+            state.waiting_receivers += 1;
+            if shared.bound == 0 {
+                // A rendezvous sender may be waiting for us to show up.
+                shared.not_full.notify_all();
+            }
+            let timed_out = match deadline {
+                None => {
+                    state = shared.not_empty.wait(state).unwrap_or_else(|e| e.into_inner());
+                    false
+                }
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        true
+                    } else {
+                        let (guard, _) = shared
+                            .not_empty
+                            .wait_timeout(state, deadline - now)
+                            .unwrap_or_else(|e| e.into_inner());
+                        state = guard;
+                        false
+                    }
+                }
+            };
+            state.waiting_receivers -= 1;
+
+            if timed_out {
+                return shared.take(&mut state).ok_or(RecvTimeoutError::Timeout);
+            }
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { rx: self }
+    }
+
+    pub fn try_iter(&self) -> TryIter<'_, T> {
+        TryIter { rx: self }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.lock().receiver = false;
+        self.shared.not_full.notify_all();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Receiver { .. }")
+    }
+}
+
+/********** Iterators **********/
+
+pub struct Iter<'a, T> {
+    rx: &'a Receiver<T>,
+}
+
+pub struct TryIter<'a, T> {
+    rx: &'a Receiver<T>,
+}
+
+pub struct IntoIter<T> {
+    rx: Receiver<T>,
+}
+
+impl<T> Iterator for Iter<'_, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+impl<T> Iterator for TryIter<'_, T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.rx.try_recv().ok()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.rx.recv().ok()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Receiver<T> {
+    type Item = T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<T> IntoIterator for Receiver<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter { rx: self }
+    }
+}
+
+#[cfg(all(test, not(feature = "loom")))]
+mod test {
+    use super::*;
+    use std::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn basics() {
+        let (tx, rx) = sync_channel(2);
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+        assert_eq!(tx.try_send(3), Err(TrySendError::Full(3)));
+
+        assert_eq!(rx.recv(), Ok(1));
+        tx.try_send(3).unwrap();
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert_eq!(rx.try_recv(), Ok(3));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+
+        drop(tx);
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Disconnected));
+        assert_eq!(rx.recv(), Err(RecvError));
+    }
+
+    #[test]
+    fn disconnect_drains_first() {
+        let (tx, rx) = sync_channel(4);
+        let tx2 = tx.clone();
+        tx.send(1).unwrap();
+        tx2.send(2).unwrap();
+        drop(tx);
+        drop(tx2);
+        assert_eq!(rx.iter().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn send_after_receiver_drop() {
+        let (tx, rx) = sync_channel(1);
+        drop(rx);
+        assert_eq!(tx.send(1), Err(SendError(1)));
+        assert_eq!(tx.try_send(2), Err(TrySendError::Disconnected(2)));
+    }
+
+    #[test]
+    fn blocked_sender_wakes_on_receiver_drop() {
+        let (tx, rx) = sync_channel(1);
+        tx.send(1).unwrap();
+        let handle = thread::spawn(move || tx.send(2));
+        thread::sleep(Duration::from_millis(20));
+        drop(rx);
+        assert_eq!(handle.join().unwrap(), Err(SendError(2)));
+    }
+
+    #[test]
+    fn recv_timeout() {
+        let (tx, rx) = sync_channel::<i32>(1);
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Timeout)
+        );
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            tx.send(7).unwrap();
+        });
+        assert_eq!(rx.recv_timeout(Duration::from_secs(10)), Ok(7));
+        handle.join().unwrap();
+        assert_eq!(
+            rx.recv_timeout(Duration::from_millis(10)),
+            Err(RecvTimeoutError::Disconnected)
+        );
+    }
+
+    #[test]
+    fn rendezvous() {
+        let (tx, rx) = sync_channel(0);
+        assert_eq!(tx.try_send(1), Err(TrySendError::Full(1)));
+
+        let handle = thread::spawn(move || {
+            for i in 0..100 {
+                tx.send(i).unwrap();
+            }
+        });
+        assert_eq!(rx.iter().collect::<Vec<_>>(), (0..100).collect::<Vec<_>>());
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn rendezvous_try_recv_takes_from_blocked_sender() {
+        let (tx, rx) = sync_channel(0);
+        let handle = thread::spawn(move || {
+            for i in 0..100 {
+                tx.send(i).unwrap();
+            }
+        });
+        let mut got = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(i) => got.push(i),
+                Err(TryRecvError::Empty) => thread::yield_now(),
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        handle.join().unwrap();
+        assert_eq!(got, (0..100).collect::<Vec<_>>());
+
+        // A held-out element goes back to the sender with the receiver.
+        let (tx, rx) = sync_channel(0);
+        let handle = thread::spawn(move || tx.send(1));
+        thread::sleep(Duration::from_millis(20));
+        drop(rx);
+        assert_eq!(handle.join().unwrap(), Err(SendError(1)));
+    }
+
+    #[test]
+    fn worker_pool() {
+        const WORKERS: usize = 8;
+        const JOBS: usize = 10_000;
+
+        let (job_tx, job_rx) = sync_channel::<usize>(WORKERS);
+        let job_rx = Arc::new(Mutex::new(job_rx));
+        let (done_tx, done_rx) = sync_channel(16);
+
+        let workers: Vec<_> = (0..WORKERS)
+            .map(|_| {
+                let job_rx = job_rx.clone();
+                let done_tx = done_tx.clone();
+                thread::spawn(move || loop {
+                    let job = job_rx.lock().unwrap().recv();
+                    match job {
+                        Ok(n) => done_tx.send(n * 2).unwrap(),
+                        Err(RecvError) => return,
+                    }
+                })
+            })
+            .collect();
+        drop(done_tx);
+
+        let feeder = thread::spawn(move || {
+            for n in 0..JOBS {
+                job_tx.send(n).unwrap();
+            }
+        });
+
+        let total: usize = done_rx.iter().sum();
+        assert_eq!(total, (0..JOBS).map(|n| n * 2).sum());
+        feeder.join().unwrap();
+        for w in workers {
+            w.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn many_producers_bounded() {
+        let (tx, rx) = sync_channel(3);
+        let producers: Vec<_> = (0..6)
+            .map(|p| {
+                let tx = tx.clone();
+                thread::spawn(move || {
+                    for i in 0..1000 {
+                        tx.send((p, i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        drop(tx);
+
+        let mut last = [None; 6];
+        let mut count = 0;
+        for (p, i) in rx {
+            assert!(last[p] < Some(i));
+            last[p] = Some(i);
+            count += 1;
+        }
+        assert_eq!(count, 6000);
+        for p in producers {
+            p.join().unwrap();
+        }
+    }
+}
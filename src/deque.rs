@@ -1,9 +1,9 @@
 #![allow(warnings)]
 
-use std::cell::RefCell;
-use std::rc::Rc;
+use alloc::rc::Rc;
+use core::cell::RefCell;
 
-struct List<T> {
+pub(crate) struct List<T> {
     head: NodePtr<T>,
     tail: NodePtr<T>,
 }
@@ -27,14 +27,14 @@ impl<T> Node<T> {
 }
 
 impl<T> List<T> {
-    fn new() -> Self {
+    pub(crate) fn new() -> Self {
         List {
             head: None,
             tail: None,
         }
     }
 
-    fn push_front(&mut self, elem: T) {
+    pub(crate) fn push_front(&mut self, elem: T) {
         let new_head = Node::new(elem);
         match self.head.take() {
             None => {
@@ -49,7 +49,7 @@ impl<T> List<T> {
         }
     }
 
-    fn pop_front(&mut self) -> Option<T> {
+    pub(crate) fn pop_front(&mut self) -> Option<T> {
         self.head.take().map(|old_head| {
             self.head = old_head.borrow_mut().next.take().map(|new_head| {
                 new_head.borrow_mut().prev = None;
@@ -62,7 +62,7 @@ impl<T> List<T> {
         })
     }
 
-    fn push_back(&mut self, elem: T) {
+    pub(crate) fn push_back(&mut self, elem: T) {
         let new_tail = Node::new(elem);
         match self.tail.take() {
             None => {
@@ -77,7 +77,7 @@ impl<T> List<T> {
         }
     }
 
-    fn pop_back(&mut self) -> Option<T> {
+    pub(crate) fn pop_back(&mut self) -> Option<T> {
         self.tail.take().map(|old_tail| {
             self.tail = old_tail.borrow_mut().prev.take().map(|new_tail| {
                 new_tail.borrow_mut().next = None;
@@ -125,7 +125,7 @@ mod test {
     }
 }
 
-struct ListIntoIter<T> {
+pub(crate) struct ListIntoIter<T> {
     list: List<T>,
 }
 
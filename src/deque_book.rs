@@ -1,71 +1,242 @@
 #![allow(unused_variables)]
-use std::cell::{Ref, RefCell, RefMut};
-use std::rc::Rc;
+use core::cell::{Ref, RefCell, RefMut};
+use core::cmp::Ordering;
+use core::fmt;
+use core::mem;
+use core::ptr;
 
-#[derive(Debug)]
-pub struct List<T> {
-    head: Link<T>,
-    tail: Link<T>,
+use crate::allocator::{AllocError, Allocator, Global};
+use crate::merge_sort::{self, Run, Sortable};
+use crate::rc::{Rc, SlotPool};
+
+// Why a try_* method refused to touch the list. The plain methods panic in
+// the same situations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListError {
+    // A node the operation needs is still borrowed, e.g. through a `Ref` from
+    // peek_front that was leaked with mem::forget.
+    Borrowed,
+    // Something besides the list holds a handle to the node being removed, so
+    // its element can't be moved out.
+    SharedNode,
+}
+
+impl fmt::Display for ListError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ListError::Borrowed => f.write_str("list node is already borrowed"),
+            ListError::SharedNode => f.write_str("list node is shared outside the list"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ListError {}
+
+pub struct List<T, A: Allocator + Clone = Global> {
+    head: Link<T, A>,
+    tail: Link<T, A>,
+    alloc: A,
+    // Popped nodes kept around for later pushes, up to pool_limit of them,
+    // linked through their own memory.
+    pool: SlotPool<RefCell<Node<T, A>>, A>,
+    pool_limit: usize,
 }
 
-type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+type Link<T, A> = Option<NodeRef<T, A>>;
+type NodeRef<T, A> = Rc<RefCell<Node<T, A>>, A>;
 
-#[derive(Debug)]
-struct Node<T> {
+struct Node<T, A: Allocator + Clone> {
     elem: T,
-    next: Link<T>,
-    prev: Link<T>,
+    next: Link<T, A>,
+    prev: Link<T, A>,
 }
 
-impl<T> Node<T> {
-    fn new(elem: T) -> Rc<RefCell<Self>> {
-        Rc::new(RefCell::new(Node {
-            elem: elem,
+impl<T, A: Allocator + Clone> Node<T, A> {
+    fn new(elem: T, alloc: &A) -> Rc<RefCell<Self>, A> {
+        Rc::new_in(
+            RefCell::new(Node {
+                elem,
+                prev: None,
+                next: None,
+            }),
+            alloc.clone(),
+        )
+    }
+
+    fn try_new(elem: T, alloc: &A) -> Result<Rc<RefCell<Self>, A>, (T, AllocError)> {
+        let node = RefCell::new(Node {
+            elem,
             prev: None,
             next: None,
-        }))
+        });
+        Rc::try_new_in(node, alloc.clone()).map_err(|(node, err)| (node.into_inner().elem, err))
     }
 }
 
 impl<T> List<T> {
     pub fn new() -> Self {
+        List::new_in(Global)
+    }
+
+    pub fn with_pool(limit: usize) -> Self {
+        List::with_pool_in(limit, Global)
+    }
+}
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    pub fn new_in(alloc: A) -> Self {
         List {
             head: None,
             tail: None,
+            alloc,
+            pool: SlotPool::new(),
+            pool_limit: 0,
+        }
+    }
+
+    /// A list that keeps up to `limit` popped nodes and reuses them for
+    /// later pushes instead of going back to the allocator. The pool itself
+    /// takes no memory beyond the nodes in it.
+    pub fn with_pool_in(limit: usize, alloc: A) -> Self {
+        let mut list = List::new_in(alloc);
+        list.pool_limit = limit;
+        list
+    }
+
+    /// Number of spare nodes currently held in the pool.
+    pub fn pooled(&self) -> usize {
+        self.pool.len()
+    }
+
+    /// Returns every pooled node to the allocator. The pool keeps its limit
+    /// and starts filling up again on the next pop.
+    pub fn shrink_to_fit(&mut self) {
+        self.pool.clear();
+    }
+
+    fn new_node(&mut self, elem: T) -> NodeRef<T, A> {
+        match self.pool.pop() {
+            Some(slot) => slot.fill(RefCell::new(Node {
+                elem,
+                prev: None,
+                next: None,
+            })),
+            None => Node::new(elem, &self.alloc),
         }
     }
 
+    fn try_new_node(&mut self, elem: T) -> Result<NodeRef<T, A>, (T, AllocError)> {
+        match self.pool.pop() {
+            Some(slot) => Ok(slot.fill(RefCell::new(Node {
+                elem,
+                prev: None,
+                next: None,
+            }))),
+            None => Node::try_new(elem, &self.alloc),
+        }
+    }
+
+    // Moves the element out of an unlinked node, keeping the node for reuse
+    // if the pool has room.
+    fn take_elem(&mut self, node: NodeRef<T, A>) -> T {
+        if self.pool.len() < self.pool_limit {
+            let (node, slot) = Rc::try_unwrap_slot(node).ok().unwrap();
+            self.pool.push(slot);
+            node.into_inner().elem
+        } else {
+            Rc::try_unwrap(node).ok().unwrap().into_inner().elem
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the current head node is borrowed.
     pub fn push_front(&mut self, elem: T) {
-        let new_head = Node::new(elem);
+        let new_head = self.new_node(elem);
+        self.link_front(new_head);
+    }
+
+    /// Like `push_front`, but hands the element back if the allocator can't
+    /// provide a node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current head node is borrowed.
+    pub fn try_push_front(&mut self, elem: T) -> Result<(), (T, AllocError)> {
+        let new_head = self.try_new_node(elem)?;
+        self.link_front(new_head);
+        Ok(())
+    }
+
+    // The old end is borrowed before anything is relinked, so if that panics
+    // the list is left as it was. Same in link_back.
+    fn link_front(&mut self, new_head: NodeRef<T, A>) {
+        if let Some(old_head) = &self.head {
+            old_head.borrow_mut().prev = Some(new_head.clone());
+        }
         match self.head.take() {
-            Some(old_head) => {
-                old_head.borrow_mut().prev = Some(new_head.clone());
-                new_head.borrow_mut().next = Some(old_head);
-                self.head = Some(new_head);
-            }
-            None => {
-                self.tail = Some(new_head.clone());
-                self.head = Some(new_head);
-            }
+            Some(old_head) => new_head.borrow_mut().next = Some(old_head),
+            None => self.tail = Some(new_head.clone()),
         }
+        self.head = Some(new_head);
     }
 
+    /// # Panics
+    ///
+    /// Panics if the current tail node is borrowed.
     pub fn push_back(&mut self, elem: T) {
-        let new_tail = Node::new(elem);
+        let new_tail = self.new_node(elem);
+        self.link_back(new_tail);
+    }
+
+    /// Like `push_back`, but hands the element back if the allocator can't
+    /// provide a node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the current tail node is borrowed.
+    pub fn try_push_back(&mut self, elem: T) -> Result<(), (T, AllocError)> {
+        let new_tail = self.try_new_node(elem)?;
+        self.link_back(new_tail);
+        Ok(())
+    }
+
+    fn link_back(&mut self, new_tail: NodeRef<T, A>) {
+        if let Some(old_tail) = &self.tail {
+            old_tail.borrow_mut().next = Some(new_tail.clone());
+        }
         match self.tail.take() {
-            Some(old_tail) => {
-                old_tail.borrow_mut().next = Some(new_tail.clone());
-                new_tail.borrow_mut().prev = Some(old_tail);
-                self.tail = Some(new_tail);
-            }
-            None => {
-                self.head = Some(new_tail.clone());
-                self.tail = Some(new_tail);
-            }
+            Some(old_tail) => new_tail.borrow_mut().prev = Some(old_tail),
+            None => self.head = Some(new_tail.clone()),
         }
+        self.tail = Some(new_tail);
     }
 
+    /// # Panics
+    ///
+    /// Panics if the tail node or its neighbour is borrowed, or if the tail
+    /// node is shared outside the list. See `try_pop_back`. The list is left
+    /// untouched when it panics.
     pub fn pop_back(&mut self) -> Option<T> {
+        self.try_pop_back().unwrap_or_else(|err| panic!("pop_back: {}", err))
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the head node or its neighbour is borrowed, or if the head
+    /// node is shared outside the list. See `try_pop_front`. The list is left
+    /// untouched when it panics.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.try_pop_front().unwrap_or_else(|err| panic!("pop_front: {}", err))
+    }
+
+    // The pops minus the checks; callers go through check_unlink first.
+    fn unlink_back(&mut self) -> Option<T> {
         self.tail.take().map(|old_tail| {
             match old_tail.borrow_mut().prev.take() {
                 Some(new_tail) => {
@@ -76,63 +247,575 @@ impl<T> List<T> {
                     self.head.take();
                 }
             }
-            Rc::try_unwrap(old_tail).ok().unwrap().into_inner().elem
+            self.take_elem(old_tail)
         })
     }
 
-    pub fn pop_front(&mut self) -> Option<T> {
-        self.head.take().map(|old_head| {
-            match old_head.borrow_mut().next.take() {
-                Some(new_head) => {
-                    new_head.borrow_mut().prev.take();
-                    self.head = Some(new_head);
-                }
-                None => {
-                    self.tail.take();
-                }
+    fn unlink_front(&mut self) -> Option<T> {
+        self.unlink_front_node().map(|old_head| self.take_elem(old_head))
+    }
+
+    fn unlink_front_node(&mut self) -> Option<NodeRef<T, A>> {
+        let old_head = self.head.take()?;
+        match old_head.borrow_mut().next.take() {
+            Some(new_head) => {
+                new_head.borrow_mut().prev.take();
+                self.head = Some(new_head);
             }
-            Rc::try_unwrap(old_head).ok().unwrap().into_inner().elem
-        })
+            None => {
+                self.tail.take();
+            }
+        }
+        Some(old_head)
     }
 
-    pub fn peek_front(&self) -> Option<Ref<T>> {
+    /// # Panics
+    ///
+    /// Panics if the head node is mutably borrowed. See `try_peek_front`.
+    pub fn peek_front(&self) -> Option<Ref<'_, T>> {
         self.head
             .as_ref()
             .map(|node| Ref::map(node.borrow(), |node| &node.elem))
     }
 
-    pub fn peek_back(&self) -> Option<Ref<T>> {
+    /// # Panics
+    ///
+    /// Panics if the tail node is mutably borrowed. See `try_peek_back`.
+    pub fn peek_back(&self) -> Option<Ref<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| Ref::map(node.borrow(), |node| &node.elem))
     }
 
-    pub fn peek_back_mut(&mut self) -> Option<RefMut<T>> {
+    /// # Panics
+    ///
+    /// Panics if the tail node is borrowed. See `try_peek_back_mut`.
+    pub fn peek_back_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.tail
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
 
-    pub fn peek_front_mut(&mut self) -> Option<RefMut<T>> {
+    /// # Panics
+    ///
+    /// Panics if the head node is borrowed. See `try_peek_front_mut`.
+    pub fn peek_front_mut(&mut self) -> Option<RefMut<'_, T>> {
         self.head
             .as_ref()
             .map(|node| RefMut::map(node.borrow_mut(), |node| &mut node.elem))
     }
+}
 
-    pub fn into_iter(self) -> IntoIter<T> {
-        IntoIter(self)
+/********** Splicing **********/
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    /// Moves every element of `other` onto the back of this list, leaving
+    /// `other` empty. The nodes are relinked, not copied; each one frees
+    /// itself through the allocator it came from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this list's tail or `other`'s head is borrowed.
+    pub fn append(&mut self, other: &mut Self) {
+        match (&self.tail, &other.head) {
+            (_, None) => return,
+            (None, Some(_)) => self.head = other.head.take(),
+            (Some(old_tail), Some(other_head)) => {
+                // Borrow both ends before relinking either, so a panic leaves
+                // both lists as they were.
+                let mut tail_node = old_tail.borrow_mut();
+                let mut head_node = other_head.borrow_mut();
+                tail_node.next = Some(other_head.clone());
+                head_node.prev = Some(old_tail.clone());
+                drop((tail_node, head_node));
+                other.head = None;
+            }
+        }
+        self.tail = other.tail.take();
+    }
+
+    /// Keeps the first `at` elements and returns the rest as a new list with
+    /// the same allocator and pool limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `at` is past the end of the list, or if a node next to the
+    /// split is mutably borrowed.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let mut rest = List::with_pool_in(self.pool_limit, self.alloc.clone());
+        if at == 0 {
+            rest.head = self.head.take();
+            rest.tail = self.tail.take();
+            return rest;
+        }
+        let mut cur = self.head.clone();
+        for _ in 1..at {
+            cur = cur.and_then(|node| {
+                let next = node.borrow().next.clone();
+                next
+            });
+        }
+        let new_tail = cur.expect("split_off index out of bounds");
+        let mut tail_node = new_tail.borrow_mut();
+        let new_head = tail_node.next.clone();
+        if let Some(new_head) = new_head {
+            // Only unhook the tail side once the head side has been borrowed.
+            new_head.borrow_mut().prev = None;
+            tail_node.next = None;
+            drop(tail_node);
+            rest.head = Some(new_head);
+            rest.tail = self.tail.replace(new_tail);
+        }
+        rest
+    }
+
+    /// Keeps only the elements for which `f` returns true, front to back.
+    ///
+    /// If `f` or an element's destructor panics, the list is left valid: a
+    /// node is fully unlinked before its element is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a node that has to be unlinked, or one of its neighbours, is
+    /// borrowed.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let keep = f(&node.borrow().elem);
+            cur = node.borrow().next.clone();
+            if !keep {
+                drop(self.unlink(node));
+            }
+        }
+    }
+
+    // Takes `node` out from anywhere in the list and moves its element out.
+    // Everything is borrowed before any link changes, so a borrow panic
+    // leaves the list intact.
+    fn unlink(&mut self, node: NodeRef<T, A>) -> T {
+        let (prev, next) = {
+            let node = node.borrow();
+            (node.prev.clone(), node.next.clone())
+        };
+        let mut prev_node = prev.as_ref().map(|prev| prev.borrow_mut());
+        let mut next_node = next.as_ref().map(|next| next.borrow_mut());
+        let mut this = node.borrow_mut();
+        this.prev = None;
+        this.next = None;
+        drop(this);
+        match &mut prev_node {
+            Some(prev_node) => prev_node.next = next.clone(),
+            None => self.head = next.clone(),
+        }
+        match &mut next_node {
+            Some(next_node) => next_node.prev = prev.clone(),
+            None => self.tail = prev.clone(),
+        }
+        drop((prev_node, next_node));
+        self.take_elem(node)
+    }
+}
+
+/********** Sorted insertion **********/
+
+// What sorted_list builds on. These walk the list, so they're O(n).
+impl<T, A: Allocator + Clone> List<T, A> {
+    // Links `elem` in after the last node, searching from the back, for
+    // which `goes_after(node's elem, &elem)` holds; at the front if none.
+    pub(crate) fn insert_by(&mut self, elem: T, mut goes_after: impl FnMut(&T, &T) -> bool) {
+        let mut after = self.tail.clone();
+        while let Some(node) = &after {
+            if goes_after(&node.borrow().elem, &elem) {
+                break;
+            }
+            let prev = node.borrow().prev.clone();
+            after = prev;
+        }
+        let new_node = self.new_node(elem);
+        let prev = match after {
+            None => return self.link_front(new_node),
+            Some(prev) => prev,
+        };
+        let next = prev.borrow().next.clone();
+        let next = match next {
+            None => return self.link_back(new_node),
+            Some(next) => next,
+        };
+        // Borrow everything before relinking anything, as in unlink.
+        let mut prev_node = prev.borrow_mut();
+        let mut next_node = next.borrow_mut();
+        let mut this = new_node.borrow_mut();
+        this.prev = Some(prev.clone());
+        this.next = Some(next.clone());
+        prev_node.next = Some(new_node.clone());
+        next_node.prev = Some(new_node.clone());
+    }
+
+    // Takes out the first node, from the front, for which `f` says Equal,
+    // giving up at the first Greater.
+    pub(crate) fn remove_by(&mut self, mut f: impl FnMut(&T) -> Ordering) -> Option<T> {
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let ord = f(&node.borrow().elem);
+            match ord {
+                Ordering::Less => cur = node.borrow().next.clone(),
+                Ordering::Equal => return Some(self.unlink(node)),
+                Ordering::Greater => return None,
+            }
+        }
+        None
+    }
+
+    // Merges `other` into this list by relinking nodes, taking other's front
+    // first only when `before(other's, ours)` says so. Leaves `other` empty.
+    // If `before` panics, nothing is lost: this list gets what was merged so
+    // far followed by its own leftovers, and `other` keeps the rest of its.
+    pub(crate) fn merge_by(&mut self, other: &mut Self, mut before: impl FnMut(&T, &T) -> bool) {
+        struct Merging<'a, T, A: Allocator + Clone> {
+            list: &'a mut List<T, A>,
+            done: List<T, A>,
+        }
+
+        impl<T, A: Allocator + Clone> Drop for Merging<'_, T, A> {
+            fn drop(&mut self) {
+                self.done.append(self.list);
+                mem::swap(&mut self.list.head, &mut self.done.head);
+                mem::swap(&mut self.list.tail, &mut self.done.tail);
+            }
+        }
+
+        let mut merging = Merging {
+            done: List::new_in(self.alloc.clone()),
+            list: self,
+        };
+        while let (Some(ours), Some(theirs)) = (&merging.list.head, &other.head) {
+            let theirs_first = before(&theirs.borrow().elem, &ours.borrow().elem);
+            let node = if theirs_first {
+                other.unlink_front_node()
+            } else {
+                merging.list.unlink_front_node()
+            };
+            merging.done.link_back(node.unwrap());
+        }
+        merging.done.append(other);
+    }
+}
+
+/********** Sorting **********/
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    /// Sorts the list by `compare`, keeping equal elements in order. The
+    /// nodes are relinked, not reallocated (see `merge_sort::sort_by`).
+    ///
+    /// # Panics
+    ///
+    /// Panics, before touching the list, if any node is borrowed.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F) {
+        self.check_unborrowed("sort_by");
+        merge_sort::sort_by(self, compare);
+    }
+
+    // Sorting borrows every node mutably, and a failed borrow halfway
+    // through would panic again while the pieces were being put back.
+    fn check_unborrowed(&self, op: &str) {
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let node = node
+                .try_borrow_mut()
+                .unwrap_or_else(|_| panic!("{}: {}", op, ListError::Borrowed));
+            cur = node.next.clone();
+        }
     }
 }
 
-impl<T> Drop for List<T> {
+#[cfg(feature = "std")]
+impl<T: Send, A: Allocator + Clone + Send> List<T, A> {
+    /// Like `sort_by`, but with a run per core sorted and merged on scoped
+    /// threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics, before touching the list, if any node is borrowed.
+    pub fn par_sort_by<F>(&mut self, compare: F)
+    where
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        self.check_unborrowed("par_sort_by");
+        let len = self.iter().count();
+        merge_sort::par_sort_by(self, len, merge_sort::par_runs(len), &compare);
+    }
+}
+
+// Nodes cut off a list that's being sorted or consumed in parallel, which
+// can move between threads: cut and spliced with split_off and append,
+// merged with merge_by.
+pub(crate) struct Chain<T, A: Allocator + Clone>(List<T, A>);
+
+// split_off unlinks both sides of a cut, so a chain's nodes are reachable
+// only through it. Chains are only made from a list held mutably with no
+// node borrowed (sorting), or owned (into_par_iter), so nothing outside can
+// reach them either. Moving a chain to another thread moves its elements and
+// its nodes' allocators and nothing else.
+unsafe impl<T: Send, A: Allocator + Clone + Send> Send for Chain<T, A> {}
+
+impl<T, A: Allocator + Clone> Run for Chain<T, A> {
+    type Elem = T;
+
+    fn empty(&self) -> Self {
+        Chain(List::new_in(self.0.alloc.clone()))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.head.is_none()
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        Chain(self.0.split_off(at))
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        self.0.append(&mut other.0);
+    }
+
+    fn merge<F: FnMut(&T, &T) -> Ordering>(&mut self, other: &mut Self, compare: &mut F) {
+        self.0
+            .merge_by(&mut other.0, |theirs, ours| compare(theirs, ours) == Ordering::Less);
+    }
+}
+
+impl<T, A: Allocator + Clone> Sortable for List<T, A> {
+    type Run = Chain<T, A>;
+
+    fn take_run(&mut self) -> Chain<T, A> {
+        Chain(self.split_off(0))
+    }
+
+    fn put_run(&mut self, mut run: Chain<T, A>) {
+        self.append(&mut run.0);
+    }
+}
+
+/********** Fallible variants **********/
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    pub fn try_pop_front(&mut self) -> Result<Option<T>, ListError> {
+        match &self.head {
+            None => Ok(None),
+            Some(old_head) => {
+                Self::check_unlink(old_head, |node| &node.next)?;
+                Ok(self.unlink_front())
+            }
+        }
+    }
+
+    pub fn try_pop_back(&mut self) -> Result<Option<T>, ListError> {
+        match &self.tail {
+            None => Ok(None),
+            Some(old_tail) => {
+                Self::check_unlink(old_tail, |node| &node.prev)?;
+                Ok(self.unlink_back())
+            }
+        }
+    }
+
+    // Checks up front everything that unlinking `end` would trip over, so a
+    // failed try_pop leaves the list untouched.
+    fn check_unlink(
+        end: &NodeRef<T, A>,
+        neighbour: impl Fn(&Node<T, A>) -> &Link<T, A>,
+    ) -> Result<(), ListError> {
+        let node = end.try_borrow_mut().map_err(|_| ListError::Borrowed)?;
+        if let Some(next) = neighbour(&node) {
+            next.try_borrow_mut().map_err(|_| ListError::Borrowed)?;
+        }
+        // The list itself holds two handles to an end node: head (or tail),
+        // plus the neighbour's link or, for a single node, the other end.
+        if Rc::strong_count(end) > 2 {
+            return Err(ListError::SharedNode);
+        }
+        Ok(())
+    }
+
+    pub fn try_peek_front(&self) -> Result<Option<Ref<'_, T>>, ListError> {
+        self.head
+            .as_ref()
+            .map(|node| node.try_borrow().map(|node| Ref::map(node, |node| &node.elem)))
+            .transpose()
+            .map_err(|_| ListError::Borrowed)
+    }
+
+    pub fn try_peek_back(&self) -> Result<Option<Ref<'_, T>>, ListError> {
+        self.tail
+            .as_ref()
+            .map(|node| node.try_borrow().map(|node| Ref::map(node, |node| &node.elem)))
+            .transpose()
+            .map_err(|_| ListError::Borrowed)
+    }
+
+    pub fn try_peek_front_mut(&mut self) -> Result<Option<RefMut<'_, T>>, ListError> {
+        self.head
+            .as_ref()
+            .map(|node| {
+                node.try_borrow_mut()
+                    .map(|node| RefMut::map(node, |node| &mut node.elem))
+            })
+            .transpose()
+            .map_err(|_| ListError::Borrowed)
+    }
+
+    pub fn try_peek_back_mut(&mut self) -> Result<Option<RefMut<'_, T>>, ListError> {
+        self.tail
+            .as_ref()
+            .map(|node| {
+                node.try_borrow_mut()
+                    .map(|node| RefMut::map(node, |node| &mut node.elem))
+            })
+            .transpose()
+            .map_err(|_| ListError::Borrowed)
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for List<T, A> {
+    fn clone(&self) -> Self {
+        let mut new_list = List::with_pool_in(self.pool_limit, self.alloc.clone());
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let node = node.borrow();
+            new_list.push_back(node.elem.clone());
+            cur = node.next.clone();
+        }
+        new_list
+    }
+}
+
+// Nodes link to each other in both directions, so a derived Debug would chase
+// prev/next forever. Walk the list front to back instead.
+impl<T: fmt::Debug, A: Allocator + Clone> fmt::Debug for List<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        let mut cur = self.head.clone();
+        while let Some(node) = cur {
+            let node = node.borrow();
+            list.entry(&node.elem);
+            cur = node.next.clone();
+        }
+        list.finish()
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T, A: Allocator + Clone> Drop for List<T, A> {
+    fn drop(&mut self) {
+        self.drop_nodes();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T, A: Allocator + Clone> Drop for List<T, A> {
     fn drop(&mut self) {
-        while self.pop_front().is_some() {}
+        self.drop_nodes();
     }
 }
 
-pub struct IntoIter<T>(List<T>);
+impl<T, A: Allocator + Clone> List<T, A> {
+    fn drop_nodes(&mut self) {
+        // If an element's destructor panics, the guard keeps popping during
+        // the unwind so the rest of the list is still freed.
+        struct DropGuard<'a, T, A: Allocator + Clone>(&'a mut List<T, A>);
 
-impl<T> Iterator for IntoIter<T> {
+        impl<T, A: Allocator + Clone> Drop for DropGuard<'_, T, A> {
+            fn drop(&mut self) {
+                // Never panic in drop: if a node can't be unlinked, leak the
+                // rest.
+                while let Ok(Some(_)) = self.0.try_pop_front() {}
+            }
+        }
+
+        // No point pooling nodes that are about to be freed.
+        self.pool_limit = 0;
+        let guard = DropGuard(self);
+        while let Ok(Some(_)) = guard.0.try_pop_front() {}
+    }
+}
+
+/// Borrows each element in turn, from either end.
+pub struct Iter<'a, T, A: Allocator + Clone = Global> {
+    front: Option<&'a RefCell<Node<T, A>>>,
+    back: Option<&'a RefCell<Node<T, A>>>,
+}
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    /// # Panics
+    ///
+    /// The iterator panics when it reaches a mutably borrowed node.
+    pub fn iter(&self) -> Iter<'_, T, A> {
+        Iter {
+            front: self.head.as_deref(),
+            back: self.tail.as_deref(),
+        }
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> Iter<'a, T, A> {
+    // Borrows the node, then steps to its neighbour. The list is borrowed
+    // for 'a, so nothing can unlink or free the nodes until then, and with
+    // the node borrowed nobody is changing its links either.
+    fn step(
+        &mut self,
+        node: &'a RefCell<Node<T, A>>,
+        neighbour: impl Fn(&Node<T, A>) -> &Link<T, A>,
+    ) -> Ref<'a, T> {
+        let elem = Ref::map(node.borrow(), |node| &node.elem);
+        // Both ends are set whenever there's a node left.
+        if ptr::eq(self.front.unwrap(), self.back.unwrap()) {
+            self.front = None;
+            self.back = None;
+        } else {
+            let next = unsafe { neighbour(&*node.as_ptr()).as_deref() };
+            if ptr::eq(node, self.front.unwrap()) {
+                self.front = next;
+            } else {
+                self.back = next;
+            }
+        }
+        elem
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> Iterator for Iter<'a, T, A> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Ref<'a, T>> {
+        let node = self.front?;
+        Some(self.step(node, |node| &node.next))
+    }
+}
+
+impl<'a, T, A: Allocator + Clone> DoubleEndedIterator for Iter<'a, T, A> {
+    fn next_back(&mut self) -> Option<Ref<'a, T>> {
+        let node = self.back?;
+        Some(self.step(node, |node| &node.prev))
+    }
+}
+
+pub struct IntoIter<T, A: Allocator + Clone = Global>(List<T, A>);
+
+impl<T, A: Allocator + Clone> IntoIterator for List<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> IntoIter<T, A> {
+        IntoIter(self)
+    }
+}
+
+impl<T, A: Allocator + Clone> Iterator for IntoIter<T, A> {
     type Item = T;
 
     fn next(&mut self) -> Option<T> {
@@ -140,15 +823,92 @@ impl<T> Iterator for IntoIter<T> {
     }
 }
 
-impl<T> DoubleEndedIterator for IntoIter<T> {
+impl<T, A: Allocator + Clone> DoubleEndedIterator for IntoIter<T, A> {
     fn next_back(&mut self) -> Option<T> {
         self.0.pop_back()
     }
 }
 
+/********** Parallel Iterator **********/
+
+#[cfg(feature = "rayon")]
+pub use self::parallel::IntoParIter;
+
+#[cfg(feature = "rayon")]
+mod parallel {
+    use rayon::iter::plumbing::{bridge, Consumer, ProducerCallback, UnindexedConsumer};
+    use rayon::prelude::*;
+
+    use super::{Chain, List};
+    use crate::allocator::{Allocator, Global};
+    use crate::par::{Detach, Owned};
+
+    impl<T: Send, A: Allocator + Clone + Send> Detach for Chain<T, A> {
+        type Item = T;
+
+        fn detach(&mut self, at: usize) -> Self {
+            Chain(self.0.split_off(at))
+        }
+
+        fn pop_front(&mut self) -> Option<T> {
+            self.0.pop_front()
+        }
+    }
+
+    // Splits by node count, cutting the list at each split; the nodes are
+    // counted once when it is created.
+    pub struct IntoParIter<T, A: Allocator + Clone = Global>(Owned<Chain<T, A>>);
+
+    impl<T: Send, A: Allocator + Clone + Send> IntoParallelIterator for List<T, A> {
+        type Item = T;
+        type Iter = IntoParIter<T, A>;
+
+        fn into_par_iter(mut self) -> IntoParIter<T, A> {
+            let len = self.iter().count();
+            IntoParIter(Owned::new(Chain(self.split_off(0)), len))
+        }
+    }
+
+    impl<T: Send, A: Allocator + Clone + Send> ParallelIterator for IntoParIter<T, A> {
+        type Item = T;
+
+        fn drive_unindexed<C: UnindexedConsumer<T>>(self, consumer: C) -> C::Result {
+            bridge(self, consumer)
+        }
+
+        fn opt_len(&self) -> Option<usize> {
+            Some(self.0.len())
+        }
+    }
+
+    impl<T: Send, A: Allocator + Clone + Send> IndexedParallelIterator for IntoParIter<T, A> {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn drive<C: Consumer<T>>(self, consumer: C) -> C::Result {
+            bridge(self, consumer)
+        }
+
+        fn with_producer<CB: ProducerCallback<T>>(self, callback: CB) -> CB::Output {
+            callback.callback(self.0)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::List;
+    use super::{List, ListError};
+    use crate::allocator::Allocator;
+    use crate::merge_sort;
+    use core::cmp::Ordering;
+    use core::mem;
+    use crate::allocator::test::{Counting, Failing};
+    use crate::allocator::AllocError;
+    use crate::panicky::{panics, Bomb, Touchy};
+    use core::cell::Cell;
+    use std::string::ToString;
+    use std::vec::Vec;
 
     #[test]
     fn basics() {
@@ -249,4 +1009,386 @@ mod test {
         dbg!(&list);
     }
 
+    #[test]
+    fn frees_every_node() {
+        let counting = Counting::default();
+        {
+            let mut list = List::new_in(&counting);
+            for i in 0..10 {
+                list.push_back(i.to_string());
+                list.push_front((-i).to_string());
+            }
+            assert_eq!(counting.live(), 20);
+
+            assert_eq!(list.pop_front(), Some("-9".to_string()));
+            assert_eq!(list.pop_back(), Some("9".to_string()));
+            assert_eq!(counting.live(), 18);
+
+            let mut iter = list.into_iter();
+            assert_eq!(iter.next_back(), Some("8".to_string()));
+        }
+        assert_eq!(counting.allocs.get(), 20);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn clone_keeps_order() {
+        let mut list = List::new();
+        list.push_back(1);
+        list.push_back(2);
+        list.push_front(0);
+
+        let copy = list.clone();
+        list.pop_back();
+        assert_eq!(format!("{:?}", copy), "[0, 1, 2]");
+        assert_eq!(format!("{:?}", list), "[0, 1]");
+    }
+
+    #[test]
+    fn try_variants() {
+        let mut list = List::new();
+        assert_eq!(list.try_pop_front(), Ok(None));
+        assert_eq!(list.try_pop_back(), Ok(None));
+        assert!(list.try_peek_front().unwrap().is_none());
+        assert!(list.try_peek_back_mut().unwrap().is_none());
+
+        list.push_back(1);
+        list.push_back(2);
+        list.push_back(3);
+
+        assert_eq!(*list.try_peek_front().unwrap().unwrap(), 1);
+        *list.try_peek_back_mut().unwrap().unwrap() = 30;
+        assert_eq!(list.try_pop_back(), Ok(Some(30)));
+        assert_eq!(list.try_pop_front(), Ok(Some(1)));
+        assert_eq!(list.try_pop_front(), Ok(Some(2)));
+        assert_eq!(list.try_pop_front(), Ok(None));
+    }
+
+    #[test]
+    fn try_variants_report_borrowed() {
+        let mut list = List::new();
+        list.push_back(1);
+        list.push_back(2);
+
+        // A leaked Ref keeps the head borrowed forever.
+        mem::forget(list.peek_front());
+
+        assert_eq!(list.try_pop_front(), Err(ListError::Borrowed));
+        assert_eq!(list.try_peek_front_mut().err(), Some(ListError::Borrowed));
+        // Shared borrows can still be taken.
+        assert_eq!(*list.try_peek_front().unwrap().unwrap(), 1);
+
+        // Popping the tail needs to touch the head's `next` link.
+        assert_eq!(list.try_pop_back(), Err(ListError::Borrowed));
+
+        // Nothing was unlinked by the failed attempts.
+        assert_eq!(format!("{:?}", list), "[1, 2]");
+        // Dropping the list must not panic either.
+    }
+
+    #[test]
+    fn try_variants_report_shared_node() {
+        let mut list = List::new();
+        list.push_back(1);
+        list.push_back(2);
+
+        let extra = list.head.clone();
+        assert_eq!(list.try_pop_front(), Err(ListError::SharedNode));
+        assert_eq!(list.try_pop_back(), Ok(Some(2)));
+        drop(extra);
+        assert_eq!(list.try_pop_front(), Ok(Some(1)));
+    }
+
+    #[test]
+    #[should_panic]
+    fn pop_front_panics_when_borrowed() {
+        let mut list = List::new();
+        list.push_back(1);
+        mem::forget(list.peek_front());
+        list.pop_front();
+    }
+
+    #[test]
+    fn try_push_sheds_on_alloc_failure() {
+        let failing = Failing::new(3);
+        let mut list = List::new_in(&failing);
+
+        assert_eq!(list.try_push_back(1), Ok(()));
+        assert_eq!(list.try_push_front(0), Ok(()));
+        assert_eq!(list.try_push_back(2), Ok(()));
+        assert_eq!(list.try_push_back(3), Err((3, AllocError)));
+        assert_eq!(list.try_push_front(-1), Err((-1, AllocError)));
+
+        // The failed pushes left the list alone.
+        assert_eq!(format!("{:?}", list), "[0, 1, 2]");
+
+        // Popping hands budget back to the allocator.
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.try_push_back(3), Ok(()));
+        assert_eq!(list.into_iter().collect::<std::vec::Vec<_>>(), [1, 2, 3]);
+        assert_eq!(failing.live(), 0);
+    }
+
+    #[test]
+    fn pool_recycles_nodes() {
+        let counting = Counting::default();
+        {
+            let mut list = List::with_pool_in(3, &counting);
+            for i in 0..5 {
+                list.push_back(i);
+            }
+            assert_eq!(counting.allocs.get(), 5);
+
+            for _ in 0..5 {
+                list.pop_front();
+            }
+            // Only `limit` nodes are kept, the rest go back to the allocator.
+            assert_eq!(list.pooled(), 3);
+            assert_eq!(counting.live(), 3);
+
+            list.push_front(10);
+            list.push_back(11);
+            assert_eq!(list.try_push_back(12), Ok(()));
+            assert_eq!(list.pooled(), 0);
+            assert_eq!(counting.allocs.get(), 5);
+
+            list.push_back(13);
+            assert_eq!(counting.allocs.get(), 6);
+            assert_eq!(list.pop_back(), Some(13));
+            assert_eq!(list.pop_front(), Some(10));
+            assert_eq!(list.pooled(), 2);
+
+            list.shrink_to_fit();
+            assert_eq!(list.pooled(), 0);
+            assert_eq!(counting.live(), 2);
+
+            // The pool starts filling again after a shrink.
+            assert_eq!(list.pop_front(), Some(11));
+            assert_eq!(list.pooled(), 1);
+        }
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn pool_allocates_nothing_of_its_own() {
+        // No buffer up front, however high the limit.
+        let failing = Failing::new(3);
+        let mut list = List::with_pool_in(usize::MAX, &failing);
+        for i in 0..3 {
+            assert_eq!(list.try_push_back(i), Ok(()));
+        }
+        assert_eq!(list.try_push_back(3), Err((3, AllocError)));
+        assert_eq!(list.pop_front(), Some(0));
+        list.shrink_to_fit();
+        assert_eq!(failing.live(), 2);
+
+        // Pooling after a shrink costs nothing either.
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.pooled(), 1);
+        assert_eq!(failing.live(), 2);
+        assert_eq!(list.try_push_front(4), Ok(()));
+        assert_eq!(list.try_push_front(5), Ok(()));
+        assert_eq!(list.try_push_front(6), Err((6, AllocError)));
+        drop(list);
+        assert_eq!(failing.live(), 0);
+
+        let counting = Counting::default();
+        let mut list = List::with_pool_in(4, &counting);
+        for i in 0..4 {
+            list.push_back(i);
+        }
+        list.shrink_to_fit();
+        while list.pop_back().is_some() {}
+        assert_eq!(list.pooled(), 4);
+        assert_eq!(counting.allocs.get(), 4);
+        list.shrink_to_fit();
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn no_pool_by_default() {
+        let counting = Counting::default();
+        let mut list = List::new_in(&counting);
+        list.push_back(1);
+        list.pop_back();
+        assert_eq!(list.pooled(), 0);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn iter() {
+        let mut list = List::new();
+        assert!(list.iter().next().is_none());
+        for i in 1..=5 {
+            list.push_back(i);
+        }
+        let front: Vec<i32> = list.iter().map(|x| *x).collect();
+        assert_eq!(front, [1, 2, 3, 4, 5]);
+        let back: Vec<i32> = list.iter().rev().map(|x| *x).collect();
+        assert_eq!(back, [5, 4, 3, 2, 1]);
+
+        // The ends meet in the middle without handing anything out twice.
+        let mut iter = list.iter();
+        assert_eq!(iter.next().map(|x| *x), Some(1));
+        assert_eq!(iter.next_back().map(|x| *x), Some(5));
+        assert_eq!(iter.next_back().map(|x| *x), Some(4));
+        assert_eq!(iter.next().map(|x| *x), Some(2));
+        assert_eq!(iter.next().map(|x| *x), Some(3));
+        assert!(iter.next().is_none());
+        assert!(iter.next_back().is_none());
+
+        // Refs from the iterator can be held side by side.
+        let all: Vec<_> = list.iter().collect();
+        assert_eq!(*all[0] + *all[4], 6);
+    }
+
+    #[test]
+    fn drop_survives_panicking_destructor() {
+        let counting = Counting::default();
+        let drops = Cell::new(0);
+        let mut list = List::with_pool_in(2, &counting);
+        for i in 0..5 {
+            list.push_back(if i == 1 { Bomb::armed(&drops) } else { Bomb::new(&drops) });
+        }
+        assert!(panics(move || drop(list)));
+        assert_eq!(drops.get(), 5);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn retain() {
+        let mut list = List::with_pool(1);
+        for i in 0..7 {
+            list.push_back(i);
+        }
+        list.retain(|&x| x % 3 != 0);
+        assert_eq!(format!("{:?}", list), "[1, 2, 4, 5]");
+        assert_eq!(list.pop_back(), Some(5));
+        assert_eq!(list.pop_front(), Some(1));
+        list.retain(|_| false);
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+    }
+
+    #[test]
+    fn retain_survives_panicking_comparison() {
+        let counting = Counting::default();
+        let mut list = List::new_in(&counting);
+        for i in [0, 1, 2, -3, 4, 5] {
+            list.push_back(Touchy(i));
+        }
+        assert!(panics(|| list.retain(|x| *x < Touchy(2))));
+        // Everything up to the panic was handled, the rest untouched.
+        assert_eq!(
+            format!("{:?}", list),
+            "[Touchy(0), Touchy(1), Touchy(-3), Touchy(4), Touchy(5)]"
+        );
+        list.push_front(Touchy(-1));
+        list.push_back(Touchy(6));
+        assert_eq!(list.pop_back(), Some(Touchy(6)));
+        assert_eq!(list.pop_front().map(|x| x.0), Some(-1));
+        drop(list);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn retain_survives_panicking_destructor() {
+        let counting = Counting::default();
+        let drops = Cell::new(0);
+        let mut list = List::new_in(&counting);
+        for i in 0..5 {
+            list.push_back((i, if i == 2 { Bomb::armed(&drops) } else { Bomb::new(&drops) }));
+        }
+        assert!(panics(|| list.retain(|&(i, _)| i % 2 == 1)));
+        // 0 and 2 were unlinked before 2's destructor went off.
+        assert_eq!(drops.get(), 2);
+        assert_eq!(list.pop_front().map(|(i, _)| i), Some(1));
+        assert_eq!(list.pop_back().map(|(i, _)| i), Some(4));
+        assert_eq!(list.pop_back().map(|(i, _)| i), Some(3));
+        assert!(list.pop_front().is_none());
+        assert_eq!(drops.get(), 5);
+        drop(list);
+        assert_eq!(counting.live(), 0);
+    }
+
+    impl<E, A: Allocator + Clone> merge_sort::test::Subject<E, A> for List<E, A> {
+        fn make(elems: Vec<E>, alloc: A) -> Self {
+            let mut list = List::new_in(alloc);
+            for elem in elems {
+                list.push_back(elem);
+            }
+            list
+        }
+
+        fn read<R>(&self, mut f: impl FnMut(&E) -> R) -> Vec<R> {
+            // The prev links visit the same nodes backwards.
+            let nodes: Vec<*const E> = self.iter().map(|x| &*x as *const E).collect();
+            let mut back: Vec<*const E> = self.iter().rev().map(|x| &*x as *const E).collect();
+            back.reverse();
+            assert_eq!(nodes, back);
+            self.iter().map(|x| f(&x)).collect()
+        }
+
+        fn sort_by(&mut self, compare: impl FnMut(&E, &E) -> Ordering) {
+            List::sort_by(self, compare);
+        }
+
+        #[cfg(feature = "std")]
+        fn par_sort_by(&mut self, compare: impl Fn(&E, &E) -> Ordering + Sync)
+        where
+            E: Send,
+            A: Send,
+        {
+            List::par_sort_by(self, compare);
+        }
+    }
+
+    #[test]
+    fn sort_by() {
+        merge_sort::test::sort_by::<List<_, _>>(&Counting::default());
+    }
+
+    #[test]
+    fn sort_by_survives_panics() {
+        merge_sort::test::sort_by_survives_panics::<List<_, _>>(&Counting::default());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn par_sort_by() {
+        merge_sort::test::par_sort_by::<List<_>>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn par_sort_by_survives_panics() {
+        merge_sort::test::par_sort_by_survives_panics::<List<_>>();
+    }
+
+    #[test]
+    fn borrow_panics_leave_list_intact() {
+        let mut list = List::new();
+        for i in 0..3 {
+            list.push_back(i);
+        }
+        let mut other = List::new();
+        other.push_back(7);
+        mem::forget(list.peek_back_mut());
+
+        assert!(panics(|| list.push_back(3)));
+        assert!(panics(|| list.pop_back()));
+        assert!(panics(|| list.append(&mut other)));
+        assert!(panics(|| list.split_off(2)));
+        assert!(panics(|| list.retain(|&x| x != 1)));
+        assert!(panics(|| list.sort_by(|a, b| b.cmp(a))));
+
+        // Nothing moved: 0 and 1 are still in place, and 2 is still stuck
+        // behind the leaked borrow.
+        let mut rest = list.split_off(1);
+        assert_eq!(list.pop_front(), Some(0));
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(*rest.peek_front().unwrap(), 1);
+        assert_eq!(rest.try_pop_front(), Err(ListError::Borrowed));
+        assert_eq!(other.pop_front(), Some(7));
+    }
 }
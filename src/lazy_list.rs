@@ -0,0 +1,365 @@
+// A persistent list like shared_list, with the same Rc-shared nodes, except
+// that each node is only worked out the first time something looks at it.
+// The list itself is a thunk: a closure that produces the first node, whose
+// next list is another thunk, and so on. Each runs at most once and its
+// result is kept, so every clone of a list sees the same elements and pays
+// for them once. Lists can be infinite as long as only a finite prefix is
+// ever looked at.
+//
+// The closures are boxed and kept until they run, so the adaptors need
+// T: 'static, and the ones that copy elements into a new list need T: Clone.
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use core::cell::{Cell, OnceCell};
+use core::fmt;
+
+pub struct LazyList<T> {
+    thunk: Rc<Thunk<T>>,
+}
+
+struct Thunk<T> {
+    memo: OnceCell<Option<Node<T>>>,
+    init: Cell<Option<Init<T>>>,
+}
+
+type Init<T> = Box<dyn FnOnce() -> Option<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: LazyList<T>,
+}
+
+impl<T> LazyList<T> {
+    pub fn new() -> Self {
+        LazyList::evaluated(None)
+    }
+
+    fn evaluated(node: Option<Node<T>>) -> Self {
+        let thunk = Thunk {
+            memo: OnceCell::from(node),
+            init: Cell::new(None),
+        };
+        LazyList {
+            thunk: Rc::new(thunk),
+        }
+    }
+
+    fn lazy<F: FnOnce() -> Option<Node<T>> + 'static>(init: F) -> Self {
+        let thunk = Thunk {
+            memo: OnceCell::new(),
+            init: Cell::new(Some(Box::new(init))),
+        };
+        LazyList {
+            thunk: Rc::new(thunk),
+        }
+    }
+
+    // Evaluates the first node if nothing has yet.
+    fn node(&self) -> Option<&Node<T>> {
+        let thunk = &*self.thunk;
+        let node = thunk.memo.get_or_init(|| {
+            let init = thunk.init.take();
+            init.expect("LazyList forced while it was being evaluated, or after that panicked")()
+        });
+        node.as_ref()
+    }
+
+    // A new list with `elem` in front of this one, already evaluated.
+    pub fn cons(&self, elem: T) -> LazyList<T> {
+        LazyList::evaluated(Some(Node {
+            elem,
+            next: self.clone(),
+        }))
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.node().map(|node| &node.elem)
+    }
+
+    // Everything after the head, not yet evaluated; empty if this is.
+    pub fn tail(&self) -> LazyList<T> {
+        match self.node() {
+            Some(node) => node.next.clone(),
+            None => LazyList::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node().is_none()
+    }
+
+    // Evaluates the whole list. Never returns for an infinite one.
+    pub fn force(&self) {
+        let mut list = self;
+        while let Some(node) = list.node() {
+            list = &node.next;
+        }
+    }
+
+    // Evaluates elements as it reaches them.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { next: Some(self) }
+    }
+}
+
+impl<T: 'static> LazyList<T> {
+    // The elements `f` returns until it returns None, each asked for when
+    // the list first gets that far.
+    pub fn from_fn<F: FnMut() -> Option<T> + 'static>(mut f: F) -> Self {
+        LazyList::lazy(move || {
+            let elem = f()?;
+            Some(Node {
+                elem,
+                next: LazyList::from_fn(f),
+            })
+        })
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U + 'static>(&self, mut f: F) -> LazyList<U> {
+        let source = self.clone();
+        LazyList::lazy(move || {
+            let node = source.node()?;
+            Some(Node {
+                elem: f(&node.elem),
+                next: node.next.map(f),
+            })
+        })
+    }
+}
+
+impl<T: Clone + 'static> LazyList<T> {
+    // seed, f(seed), f(f(seed)) and so on, forever.
+    pub fn iterate<F: FnMut(&T) -> T + 'static>(seed: T, mut f: F) -> Self {
+        let mut seed = Some(seed);
+        let mut last: Option<T> = None;
+        LazyList::from_fn(move || {
+            let elem = match last.take() {
+                Some(last) => f(&last),
+                None => seed.take()?,
+            };
+            last = Some(elem.clone());
+            Some(elem)
+        })
+    }
+
+    pub fn repeat(elem: T) -> Self {
+        LazyList::from_fn(move || Some(elem.clone()))
+    }
+
+    // Evaluating each node of the result evaluates this list up to the next
+    // element that passes, however far that is.
+    pub fn filter<F: FnMut(&T) -> bool + 'static>(&self, mut pred: F) -> LazyList<T> {
+        let mut source = self.clone();
+        LazyList::lazy(move || loop {
+            let node = source.node()?;
+            if pred(&node.elem) {
+                return Some(Node {
+                    elem: node.elem.clone(),
+                    next: node.next.filter(pred),
+                });
+            }
+            let next = node.next.clone();
+            source = next;
+        })
+    }
+
+    pub fn take(&self, n: usize) -> LazyList<T> {
+        if n == 0 {
+            return LazyList::new();
+        }
+        let source = self.clone();
+        LazyList::lazy(move || {
+            let node = source.node()?;
+            Some(Node {
+                elem: node.elem.clone(),
+                next: node.next.take(n - 1),
+            })
+        })
+    }
+
+    pub fn zip<U: Clone + 'static>(&self, other: &LazyList<U>) -> LazyList<(T, U)> {
+        let (ours, theirs) = (self.clone(), other.clone());
+        LazyList::lazy(move || {
+            let (ours, theirs) = (ours.node()?, theirs.node()?);
+            Some(Node {
+                elem: (ours.elem.clone(), theirs.elem.clone()),
+                next: ours.next.zip(&theirs.next),
+            })
+        })
+    }
+}
+
+// Unlinks evaluated nodes one at a time, where dropping each node's next
+// list from inside its own drop would use stack in proportion to the
+// length. Stops at the first node someone else still holds.
+impl<T> Drop for LazyList<T> {
+    fn drop(&mut self) {
+        let take = |list: &mut LazyList<T>| {
+            Rc::get_mut(&mut list.thunk)
+                .and_then(|thunk| thunk.memo.take())
+                .flatten()
+        };
+        let mut node = match take(self) {
+            Some(node) => node,
+            None => return,
+        };
+        // The node being replaced has had its next list emptied, so
+        // dropping it doesn't go any further.
+        while let Some(next) = take(&mut node.next) {
+            node = next;
+        }
+    }
+}
+
+// Cloning shares every node, evaluated or not.
+impl<T> Clone for LazyList<T> {
+    fn clone(&self) -> Self {
+        LazyList {
+            thunk: self.thunk.clone(),
+        }
+    }
+}
+
+impl<T> Default for LazyList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Shows only what has been evaluated, with `..` for the rest.
+impl<T: fmt::Debug> fmt::Debug for LazyList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries = f.debug_list();
+        let mut list = self;
+        loop {
+            match list.thunk.memo.get() {
+                Some(Some(node)) => {
+                    entries.entry(&node.elem);
+                    list = &node.next;
+                }
+                Some(None) => return entries.finish(),
+                None => return entries.finish_non_exhaustive(),
+            }
+        }
+    }
+}
+
+/********** Iterator **********/
+
+pub struct Iter<'a, T> {
+    next: Option<&'a LazyList<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.next?.node();
+        self.next = node.map(|node| &node.next);
+        node.map(|node| &node.elem)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LazyList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::LazyList;
+    use crate::panicky::panics;
+    use core::cell::Cell;
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    fn collect<T: Clone>(list: &LazyList<T>) -> Vec<T> {
+        list.iter().cloned().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let empty = LazyList::<i32>::new();
+        assert!(empty.is_empty());
+        assert_eq!(empty.head(), None);
+        assert!(empty.tail().is_empty());
+
+        let list = empty.cons(3).cons(2).cons(1);
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(list.tail().head(), Some(&2));
+        assert_eq!(collect(&list), [1, 2, 3]);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn evaluates_each_node_once() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let list = LazyList::from_fn(move || {
+            counter.set(counter.get() + 1);
+            Some(counter.get()).filter(|&n| n <= 5)
+        });
+        assert_eq!(calls.get(), 0);
+        assert_eq!(format!("{:?}", list), "[..]");
+
+        let copy = list.clone();
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(copy.tail().head(), Some(&2));
+        assert_eq!(calls.get(), 2);
+        assert_eq!(format!("{:?}", copy), "[1, 2, ..]");
+
+        list.force();
+        assert_eq!(calls.get(), 6);
+        assert_eq!(collect(&copy), [1, 2, 3, 4, 5]);
+        assert_eq!(calls.get(), 6);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3, 4, 5]");
+    }
+
+    #[test]
+    fn infinite_lists() {
+        let naturals = LazyList::iterate(0u64, |n| n + 1);
+        let evens = naturals.filter(|n| n % 2 == 0);
+        let squares = naturals.map(|n| n * n);
+        assert_eq!(collect(&evens.take(4)), [0, 2, 4, 6]);
+        assert_eq!(collect(&squares.take(4)), [0, 1, 4, 9]);
+        assert_eq!(
+            collect(&naturals.zip(&LazyList::repeat('x')).take(2)),
+            [(0, 'x'), (1, 'x')]
+        );
+        // Only as far as anything looked.
+        assert_eq!(format!("{:?}", naturals), "[0, 1, 2, 3, 4, 5, 6, ..]");
+
+        let fibs = LazyList::iterate((0u64, 1u64), |&(a, b)| (b, a + b)).map(|&(a, _)| a);
+        assert_eq!(collect(&fibs.take(10)), [0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+        assert!(fibs.take(0).is_empty());
+
+        let short = LazyList::new().cons(2).cons(1);
+        assert_eq!(collect(&short.zip(&naturals)), [(1, 0), (2, 1)]);
+        assert_eq!(collect(&short.take(5)), [1, 2]);
+    }
+
+    #[test]
+    fn long_chains_drop_without_recursing() {
+        let n = 1_000_000;
+        let list = LazyList::iterate(0usize, |n| n + 1).take(n);
+        list.force();
+        let mapped = list.map(|n| n + 1);
+        mapped.force();
+        assert_eq!(mapped.iter().count(), n);
+        drop(list);
+        drop(mapped);
+    }
+
+    #[test]
+    fn a_panicking_thunk_stays_unevaluated() {
+        let list = LazyList::<i32>::from_fn(|| panic!("nope"));
+        assert!(panics(|| list.force()));
+        assert_eq!(format!("{:?}", list), "[..]");
+        assert!(panics(|| list.force()));
+    }
+}
@@ -1,4 +1,53 @@
-mod sll;
-mod shared_list;
+#![no_std]
+// With the nightly feature, the Drop impls of the owning lists mark their
+// element type #[may_dangle]: dropping a list drops its elements but never
+// reads them, so a list may outlive data its elements borrow. Each list keeps
+// a PhantomData that owns T, so dropck still checks T's own destructor.
+#![cfg_attr(feature = "nightly", feature(dropck_eyepatch))]
+
+extern crate alloc;
+
+#[cfg(any(feature = "std", test))]
+#[cfg_attr(test, macro_use)]
+extern crate std;
+
+pub mod sll;
+pub mod shared_list;
+pub mod ra_list;
+pub mod vector;
+pub mod zipper;
+pub mod lazy_list;
 mod deque;
-mod deque_book;
+pub mod deque_book;
+
+pub mod allocator;
+mod rc;
+mod merge_sort;
+
+pub mod static_list;
+pub mod sorted_list;
+pub mod ring;
+pub mod xor_list;
+
+#[cfg(test)]
+mod model_test;
+#[cfg(test)]
+mod panicky;
+
+// The lock-free lists only need pointer-sized CAS, so they are available
+// without std on any target that has it.
+#[cfg(target_has_atomic = "ptr")]
+mod atomic;
+#[cfg(target_has_atomic = "ptr")]
+mod reclaim;
+#[cfg(target_has_atomic = "ptr")]
+pub mod treiber;
+#[cfg(target_has_atomic = "ptr")]
+pub mod ms_queue;
+
+#[cfg(feature = "std")]
+pub mod channel;
+#[cfg(feature = "async")]
+pub mod async_queue;
+#[cfg(feature = "rayon")]
+mod par;
@@ -0,0 +1,326 @@
+// The merge sort behind sort_by and par_sort_by on sll::List,
+// deque_book::List and XorList. A list lends its nodes out as a Run, which
+// can be cut, spliced and merged by relinking alone, and gets them back when
+// the sort is done or has panicked, so a panicking `compare` never loses an
+// element.
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::mem;
+
+pub(crate) trait Run: Sized {
+    type Elem;
+
+    // An empty run that can be spliced onto this one.
+    fn empty(&self) -> Self;
+
+    fn is_empty(&self) -> bool;
+
+    // Keeps the first `at` nodes and returns the rest.
+    fn split_off(&mut self, at: usize) -> Self;
+
+    // Links all of `other` on after this run, leaving it empty.
+    fn append(&mut self, other: &mut Self);
+
+    // Merges the sorted `other`, whose nodes came after this run's, into this
+    // sorted run, taking from this one on ties. If `compare` panics, the two
+    // runs still hold every node between them.
+    fn merge<F: FnMut(&Self::Elem, &Self::Elem) -> Ordering>(
+        &mut self,
+        other: &mut Self,
+        compare: &mut F,
+    );
+}
+
+pub(crate) trait Sortable {
+    type Run: Run;
+
+    // Every node, leaving the list empty.
+    fn take_run(&mut self) -> Self::Run;
+
+    // Hands the nodes back to the list take_run emptied.
+    fn put_run(&mut self, run: Self::Run);
+}
+
+type Elem<L> = <<L as Sortable>::Run as Run>::Elem;
+
+// Stable merge sort that relinks the nodes instead of moving elements. If
+// `compare` panics, the list still holds every element, in some order.
+pub(crate) fn sort_by<L, F>(list: &mut L, mut compare: F)
+where
+    L: Sortable,
+    F: FnMut(&Elem<L>, &Elem<L>) -> Ordering,
+{
+    with_run(list, |run| sort_run(run, &mut compare));
+}
+
+// Lends the list's nodes to `sort`, and takes them back even if it panics.
+fn with_run<L: Sortable>(list: &mut L, sort: impl FnOnce(&mut L::Run)) {
+    struct Lent<'a, L: Sortable> {
+        list: &'a mut L,
+        run: Option<L::Run>,
+    }
+
+    impl<L: Sortable> Drop for Lent<'_, L> {
+        fn drop(&mut self) {
+            if let Some(run) = self.run.take() {
+                self.list.put_run(run);
+            }
+        }
+    }
+
+    let run = list.take_run();
+    let mut lent = Lent {
+        list,
+        run: Some(run),
+    };
+    sort(lent.run.as_mut().unwrap());
+}
+
+// Bottom-up merge sort: bins[i] is empty or holds a sorted run of 2^i nodes,
+// each run made of earlier nodes than the ones below it. Every node is
+// always in the input, a bin or `carry`, and dropping the sorter splices
+// them all back into `*run`.
+fn sort_run<R: Run, F: FnMut(&R::Elem, &R::Elem) -> Ordering>(run: &mut R, compare: &mut F) {
+    struct Sorter<'a, R: Run> {
+        run: &'a mut R,
+        input: R,
+        carry: R,
+        bins: Vec<R>,
+    }
+
+    impl<R: Run> Drop for Sorter<'_, R> {
+        fn drop(&mut self) {
+            self.run.append(&mut self.carry);
+            for bin in self.bins.iter_mut() {
+                self.run.append(bin);
+            }
+            self.run.append(&mut self.input);
+        }
+    }
+
+    let empty = run.empty();
+    let input = mem::replace(run, empty);
+    let mut s = Sorter {
+        carry: run.empty(),
+        bins: Vec::new(),
+        input,
+        run,
+    };
+    while !s.input.is_empty() {
+        let rest = s.input.split_off(1);
+        s.carry = mem::replace(&mut s.input, rest);
+        let mut i = 0;
+        while i < s.bins.len() && !s.bins[i].is_empty() {
+            s.bins[i].merge(&mut s.carry, compare);
+            mem::swap(&mut s.carry, &mut s.bins[i]);
+            i += 1;
+        }
+        if i == s.bins.len() {
+            let empty = s.input.empty();
+            s.bins.push(empty);
+        }
+        mem::swap(&mut s.bins[i], &mut s.carry);
+    }
+    for i in 0..s.bins.len() {
+        if !s.bins[i].is_empty() {
+            s.bins[i].merge(&mut s.carry, compare);
+            mem::swap(&mut s.carry, &mut s.bins[i]);
+        }
+    }
+}
+
+// Below this many nodes per thread, par_sort_by just sorts sequentially.
+#[cfg(feature = "std")]
+const PAR_MIN_RUN: usize = 4096;
+
+// How many runs par_sort_by cuts a list of `len` nodes into: one per core,
+// but none shorter than PAR_MIN_RUN.
+#[cfg(feature = "std")]
+pub(crate) fn par_runs(len: usize) -> usize {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    threads.min(len / PAR_MIN_RUN)
+}
+
+// Like sort_by, but cuts the list's `len` nodes into `runs` runs, sorts them
+// on scoped threads and then merges them pairwise, also on scoped threads.
+#[cfg(feature = "std")]
+pub(crate) fn par_sort_by<L, F>(list: &mut L, len: usize, runs: usize, compare: &F)
+where
+    L: Sortable,
+    L::Run: Send,
+    F: Fn(&Elem<L>, &Elem<L>) -> Ordering + Sync,
+{
+    if runs < 2 || len < runs {
+        return sort_by(list, compare);
+    }
+
+    // Holds the runs while they're cut apart and splices them back, in
+    // order, when done or when a thread panics.
+    struct Runs<'a, R: Run> {
+        run: &'a mut R,
+        runs: Vec<R>,
+    }
+
+    impl<R: Run> Drop for Runs<'_, R> {
+        fn drop(&mut self) {
+            for run in self.runs.iter_mut() {
+                self.run.append(run);
+            }
+        }
+    }
+
+    with_run(list, |run| {
+        let empty = run.empty();
+        let whole = mem::replace(run, empty);
+        let mut guard = Runs {
+            run,
+            runs: Vec::with_capacity(runs),
+        };
+        guard.runs.push(whole);
+        for i in 0..runs - 1 {
+            let len = len / runs + usize::from(i < len % runs);
+            let rest = guard.runs.last_mut().unwrap().split_off(len);
+            guard.runs.push(rest);
+        }
+
+        on_threads(guard.runs.iter_mut(), |run| sort_run(run, &mut |a, b| compare(a, b)));
+        while guard.runs.len() > 1 {
+            on_threads(guard.runs.chunks_mut(2), |pair| {
+                if let [left, right] = pair {
+                    left.merge(right, &mut |a, b| compare(a, b));
+                }
+            });
+            guard.runs.retain(|run| !run.is_empty());
+        }
+    });
+}
+
+// Runs `work` on a scoped thread per item, then rethrows the first panic
+// once every thread is done.
+#[cfg(feature = "std")]
+fn on_threads<I: Send>(items: impl Iterator<Item = I>, work: impl Fn(I) + Sync) {
+    let work = &work;
+    std::thread::scope(|s| {
+        let handles: Vec<_> = items.map(|item| s.spawn(move || work(item))).collect();
+        let mut panic = None;
+        for handle in handles {
+            if let Err(payload) = handle.join() {
+                panic.get_or_insert(payload);
+            }
+        }
+        if let Some(payload) = panic {
+            std::panic::resume_unwind(payload);
+        }
+    });
+}
+
+// The sort tests every Sortable list runs; each list's test module calls
+// them with its own type.
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::allocator::test::Counting;
+    #[cfg(feature = "std")]
+    use crate::allocator::Global;
+    use crate::panicky::{panics, Touchy};
+
+    pub(crate) trait Subject<E, A>: Sortable {
+        // A list of `elems`, front to back.
+        fn make(elems: Vec<E>, alloc: A) -> Self;
+
+        // `f` of each element front to back, checking that any links the
+        // other way agree.
+        fn read<R>(&self, f: impl FnMut(&E) -> R) -> Vec<R>;
+
+        fn sort_by(&mut self, compare: impl FnMut(&E, &E) -> Ordering);
+
+        #[cfg(feature = "std")]
+        fn par_sort_by(&mut self, compare: impl Fn(&E, &E) -> Ordering + Sync)
+        where
+            E: Send,
+            A: Send;
+    }
+
+    type Key = (u32, u32);
+
+    // (key, position) pairs in a scrambled order; sorting by key alone must
+    // keep equal keys in their original order.
+    fn scrambled<L: Subject<Key, A>, A>(n: u32, alloc: A) -> L {
+        L::make((0..n).map(|i| (i.wrapping_mul(2_654_435_761) % 97, i)).collect(), alloc)
+    }
+
+    fn sorted_copy<L: Subject<Key, A>, A>(list: &L) -> Vec<Key> {
+        let mut elems = list.read(|&x| x);
+        elems.sort_by_key(|&(key, _)| key);
+        elems
+    }
+
+    fn by_key(a: &Key, b: &Key) -> Ordering {
+        a.0.cmp(&b.0)
+    }
+
+    // Sorted, stably, with the same nodes.
+    pub(crate) fn sort_by<'a, L: Subject<Key, &'a Counting>>(counting: &'a Counting) {
+        for n in [0, 1, 2, 3, 17, 1000] {
+            let mut list: L = scrambled(n, counting);
+            let expected = sorted_copy(&list);
+            list.sort_by(by_key);
+            assert_eq!(list.read(|&x| x), expected);
+            assert_eq!(counting.live(), n as usize);
+            drop(list);
+            assert_eq!(counting.live(), 0);
+        }
+    }
+
+    pub(crate) fn sort_by_survives_panics<'a, L: Subject<Touchy, &'a Counting>>(
+        counting: &'a Counting,
+    ) {
+        let elems = [5, 4, -3, 2, 1, 0, 7].iter().map(|&i| Touchy(i)).collect();
+        let mut list = L::make(elems, counting);
+        assert!(panics(|| list.sort_by(|a, b| a.cmp(b))));
+        let mut left = list.read(|x| x.0);
+        left.sort();
+        assert_eq!(left, [-3, 0, 1, 2, 4, 5, 7]);
+        drop(list);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn par_sort_by<L>()
+    where
+        L: Subject<Key, Global>,
+        L::Run: Run<Elem = Key> + Send,
+    {
+        let mut list: L = scrambled(100_000, Global);
+        let expected = sorted_copy(&list);
+        list.par_sort_by(by_key);
+        assert_eq!(list.read(|&x| x), expected);
+
+        // Whatever the machine's core count, try odd numbers of runs and
+        // runs of a single node.
+        for (n, runs) in [(1000, 2), (1000, 3), (1001, 7), (5, 5), (3, 8)] {
+            let mut list: L = scrambled(n, Global);
+            let expected = sorted_copy(&list);
+            super::par_sort_by(&mut list, n as usize, runs, &by_key);
+            assert_eq!(list.read(|&x| x), expected);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn par_sort_by_survives_panics<L>()
+    where
+        L: Subject<Touchy, Global>,
+        L::Run: Run<Elem = Touchy> + Send,
+    {
+        let touchy = |i| Touchy(if i == 777 { -1 } else { i });
+        let mut list = L::make((0..1000).map(touchy).collect(), Global);
+        let compare = |a: &Touchy, b: &Touchy| a.cmp(b);
+        assert!(panics(|| super::par_sort_by(&mut list, 1000, 4, &compare)));
+        let mut left = list.read(|x| x.0);
+        left.sort();
+        let mut expected: Vec<_> = (0..1000).map(|i| touchy(i).0).collect();
+        expected.sort();
+        assert_eq!(left, expected);
+    }
+}
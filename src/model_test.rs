@@ -0,0 +1,556 @@
+// Runs random operation sequences against each list and against a VecDeque
+// model, checking after every step that the two agree. proptest shrinks any
+// failure down to a minimal sequence of ops.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::Union;
+use std::collections::VecDeque;
+use std::vec::Vec;
+
+use crate::static_list::{StaticDeque, StaticList};
+use crate::xor_list::XorList;
+use crate::vector::Vector;
+use crate::{deque, deque_book, ra_list, shared_list, sll};
+
+#[derive(Clone, Debug)]
+enum Op {
+    PushFront(i32),
+    PushBack(i32),
+    PopFront,
+    PopBack,
+    PeekFront,
+    PeekBack,
+    Iter,
+    // Reduced modulo len + 1 so it always lands inside the list.
+    SplitOff(usize),
+    Append(Vec<i32>),
+}
+
+// The operations a list supports, in terms the model understands. Lists only
+// implement what they have; the BACK/SPLICE flags keep the other ops out of
+// their generated sequences.
+trait Subject: Sized {
+    const BACK: bool = false;
+    const SPLICE: bool = false;
+    const CAPACITY: usize = usize::MAX;
+
+    fn from_slice(elems: &[i32]) -> Self;
+    // Returns whether the element went in; only fixed-capacity lists refuse.
+    fn push_front(&mut self, elem: i32) -> bool;
+    fn pop_front(&mut self) -> Option<i32>;
+    fn peek_front(&mut self) -> Option<i32>;
+    // Front to back. Peeking and this take &mut so lists without a peek or
+    // a borrowing iterator can pop and put things back.
+    fn contents(&mut self) -> Vec<i32>;
+
+    fn push_back(&mut self, _elem: i32) -> bool {
+        unreachable!()
+    }
+    fn pop_back(&mut self) -> Option<i32> {
+        unreachable!()
+    }
+    fn peek_back(&mut self) -> Option<i32> {
+        unreachable!()
+    }
+    fn split_off(&mut self, _at: usize) -> Self {
+        unreachable!()
+    }
+    fn append(&mut self, _other: &mut Self) {
+        unreachable!()
+    }
+    // Replaces the element at `at`, which is in bounds. Only the persistent
+    // lists have it, and only set_persists calls it.
+    fn set(&mut self, _at: usize, _elem: i32) {
+        unreachable!()
+    }
+}
+
+fn ops<S: Subject>() -> impl Strategy<Value = Vec<Op>> {
+    let mut choices = vec![
+        any::<i32>().prop_map(Op::PushFront).boxed(),
+        Just(Op::PopFront).boxed(),
+        Just(Op::PeekFront).boxed(),
+        Just(Op::Iter).boxed(),
+    ];
+    if S::BACK {
+        choices.push(any::<i32>().prop_map(Op::PushBack).boxed());
+        choices.push(Just(Op::PopBack).boxed());
+        choices.push(Just(Op::PeekBack).boxed());
+    }
+    if S::SPLICE {
+        choices.push(any::<usize>().prop_map(Op::SplitOff).boxed());
+        choices.push(vec(any::<i32>(), 0..8).prop_map(Op::Append).boxed());
+    }
+    vec(Union::new(choices), 0..64)
+}
+
+fn check<S: Subject>(ops: &[Op]) -> Result<(), TestCaseError> {
+    let mut list = S::from_slice(&[]);
+    let mut model = VecDeque::new();
+    for op in ops {
+        match op {
+            Op::PushFront(elem) => {
+                let fits = model.len() < S::CAPACITY;
+                prop_assert_eq!(list.push_front(*elem), fits);
+                if fits {
+                    model.push_front(*elem);
+                }
+            }
+            Op::PushBack(elem) => {
+                let fits = model.len() < S::CAPACITY;
+                prop_assert_eq!(list.push_back(*elem), fits);
+                if fits {
+                    model.push_back(*elem);
+                }
+            }
+            Op::PopFront => prop_assert_eq!(list.pop_front(), model.pop_front()),
+            Op::PopBack => prop_assert_eq!(list.pop_back(), model.pop_back()),
+            Op::PeekFront => prop_assert_eq!(list.peek_front(), model.front().copied()),
+            Op::PeekBack => prop_assert_eq!(list.peek_back(), model.back().copied()),
+            Op::Iter => prop_assert_eq!(list.contents(), Vec::from(model.clone())),
+            Op::SplitOff(at) => {
+                let at = at % (model.len() + 1);
+                let mut rest = list.split_off(at);
+                prop_assert_eq!(rest.contents(), Vec::from(model.split_off(at)));
+            }
+            Op::Append(elems) => {
+                let mut other = S::from_slice(elems);
+                list.append(&mut other);
+                prop_assert_eq!(other.contents(), Vec::<i32>::new());
+                model.extend(elems);
+            }
+        }
+    }
+    prop_assert_eq!(list.contents(), Vec::from(model));
+    Ok(())
+}
+
+// Every version made by set is its own; the ones before it don't change.
+fn set_persists<S: Subject + Clone>(
+    elems: &[i32],
+    sets: &[(usize, i32)],
+) -> Result<(), TestCaseError> {
+    let mut list = S::from_slice(elems);
+    let mut model = elems.to_vec();
+    let mut versions = Vec::new();
+    for &(at, elem) in sets {
+        let at = at % model.len();
+        list.set(at, elem);
+        model[at] = elem;
+        versions.push((list.clone(), model.clone()));
+    }
+    for (mut version, expected) in versions {
+        prop_assert_eq!(version.contents(), expected);
+    }
+    Ok(())
+}
+
+/********** Subjects **********/
+
+impl Subject for sll::List<i32> {
+    const SPLICE: bool = true;
+
+    fn from_slice(elems: &[i32]) -> Self {
+        let mut list = sll::List::new();
+        for &elem in elems.iter().rev() {
+            list.push(elem);
+        }
+        list
+    }
+    fn push_front(&mut self, elem: i32) -> bool {
+        self.push(elem);
+        true
+    }
+    fn pop_front(&mut self) -> Option<i32> {
+        self.pop()
+    }
+    fn peek_front(&mut self) -> Option<i32> {
+        self.peek().copied()
+    }
+    fn contents(&mut self) -> Vec<i32> {
+        self.iter().copied().collect()
+    }
+    fn split_off(&mut self, at: usize) -> Self {
+        sll::List::split_off(self, at)
+    }
+    fn append(&mut self, other: &mut Self) {
+        sll::List::append(self, other)
+    }
+}
+
+impl Subject for deque::List<i32> {
+    const BACK: bool = true;
+
+    fn from_slice(elems: &[i32]) -> Self {
+        let mut list = deque::List::new();
+        for &elem in elems {
+            list.push_back(elem);
+        }
+        list
+    }
+    fn push_front(&mut self, elem: i32) -> bool {
+        deque::List::push_front(self, elem);
+        true
+    }
+    fn push_back(&mut self, elem: i32) -> bool {
+        deque::List::push_back(self, elem);
+        true
+    }
+    fn pop_front(&mut self) -> Option<i32> {
+        deque::List::pop_front(self)
+    }
+    fn pop_back(&mut self) -> Option<i32> {
+        deque::List::pop_back(self)
+    }
+    fn peek_front(&mut self) -> Option<i32> {
+        let elem = deque::List::pop_front(self)?;
+        deque::List::push_front(self, elem);
+        Some(elem)
+    }
+    fn peek_back(&mut self) -> Option<i32> {
+        let elem = deque::List::pop_back(self)?;
+        deque::List::push_back(self, elem);
+        Some(elem)
+    }
+    fn contents(&mut self) -> Vec<i32> {
+        let mut elems = Vec::new();
+        while let Some(elem) = deque::List::pop_front(self) {
+            elems.push(elem);
+        }
+        for &elem in &elems {
+            deque::List::push_back(self, elem);
+        }
+        elems
+    }
+}
+
+impl Subject for deque_book::List<i32> {
+    const BACK: bool = true;
+    const SPLICE: bool = true;
+
+    fn from_slice(elems: &[i32]) -> Self {
+        let mut list = deque_book::List::new();
+        for &elem in elems {
+            list.push_back(elem);
+        }
+        list
+    }
+    fn push_front(&mut self, elem: i32) -> bool {
+        deque_book::List::push_front(self, elem);
+        true
+    }
+    fn push_back(&mut self, elem: i32) -> bool {
+        deque_book::List::push_back(self, elem);
+        true
+    }
+    fn pop_front(&mut self) -> Option<i32> {
+        deque_book::List::pop_front(self)
+    }
+    fn pop_back(&mut self) -> Option<i32> {
+        deque_book::List::pop_back(self)
+    }
+    fn peek_front(&mut self) -> Option<i32> {
+        deque_book::List::peek_front(self).map(|elem| *elem)
+    }
+    fn peek_back(&mut self) -> Option<i32> {
+        deque_book::List::peek_back(self).map(|elem| *elem)
+    }
+    fn contents(&mut self) -> Vec<i32> {
+        self.clone().into_iter().collect()
+    }
+    fn split_off(&mut self, at: usize) -> Self {
+        deque_book::List::split_off(self, at)
+    }
+    fn append(&mut self, other: &mut Self) {
+        deque_book::List::append(self, other)
+    }
+}
+
+impl Subject for shared_list::List<i32> {
+    fn from_slice(elems: &[i32]) -> Self {
+        elems
+            .iter()
+            .rev()
+            .fold(shared_list::List::new(), |list, &elem| list.append(elem))
+    }
+    fn push_front(&mut self, elem: i32) -> bool {
+        *self = shared_list::List::append(self, elem);
+        true
+    }
+    fn pop_front(&mut self) -> Option<i32> {
+        let elem = self.head().copied();
+        *self = self.tail();
+        elem
+    }
+    fn peek_front(&mut self) -> Option<i32> {
+        self.head().copied()
+    }
+    fn contents(&mut self) -> Vec<i32> {
+        self.iter().copied().collect()
+    }
+}
+
+impl Subject for XorList<i32> {
+    const BACK: bool = true;
+
+    fn from_slice(elems: &[i32]) -> Self {
+        elems.iter().copied().collect()
+    }
+    fn push_front(&mut self, elem: i32) -> bool {
+        XorList::push_front(self, elem);
+        true
+    }
+    fn push_back(&mut self, elem: i32) -> bool {
+        XorList::push_back(self, elem);
+        true
+    }
+    fn pop_front(&mut self) -> Option<i32> {
+        XorList::pop_front(self)
+    }
+    fn pop_back(&mut self) -> Option<i32> {
+        XorList::pop_back(self)
+    }
+    fn peek_front(&mut self) -> Option<i32> {
+        XorList::peek_front(self).copied()
+    }
+    fn peek_back(&mut self) -> Option<i32> {
+        XorList::peek_back(self).copied()
+    }
+    fn contents(&mut self) -> Vec<i32> {
+        let front: Vec<i32> = self.iter().copied().collect();
+        let mut back: Vec<i32> = self.iter().rev().copied().collect();
+        back.reverse();
+        assert_eq!(front, back);
+        assert_eq!(self.len(), front.len());
+        front
+    }
+}
+
+impl Subject for ra_list::List<i32> {
+    fn from_slice(elems: &[i32]) -> Self {
+        elems.iter().copied().collect()
+    }
+    fn push_front(&mut self, elem: i32) -> bool {
+        *self = self.cons(elem);
+        true
+    }
+    fn pop_front(&mut self) -> Option<i32> {
+        let elem = self.head().copied();
+        *self = self.tail();
+        elem
+    }
+    fn peek_front(&mut self) -> Option<i32> {
+        self.head().copied()
+    }
+    fn contents(&mut self) -> Vec<i32> {
+        let elems: Vec<i32> = self.iter().copied().collect();
+        let indexed: Vec<i32> = (0..self.len()).map(|i| *self.get(i).unwrap()).collect();
+        assert_eq!(elems, indexed);
+        elems
+    }
+    fn set(&mut self, at: usize, elem: i32) {
+        *self = ra_list::List::set(self, at, elem);
+    }
+}
+
+// Both ends go through concat and split_at.
+impl Subject for Vector<i32> {
+    const BACK: bool = true;
+    const SPLICE: bool = true;
+
+    fn from_slice(elems: &[i32]) -> Self {
+        elems.iter().copied().collect()
+    }
+    fn push_front(&mut self, elem: i32) -> bool {
+        *self = Vector::new().push_back(elem).concat(self);
+        true
+    }
+    fn pop_front(&mut self) -> Option<i32> {
+        let elem = self.get(0).copied()?;
+        *self = self.split_at(1).1;
+        Some(elem)
+    }
+    fn peek_front(&mut self) -> Option<i32> {
+        self.get(0).copied()
+    }
+    fn contents(&mut self) -> Vec<i32> {
+        let elems: Vec<i32> = self.iter().copied().collect();
+        let indexed: Vec<i32> = (0..self.len()).map(|i| *self.get(i).unwrap()).collect();
+        assert_eq!(elems, indexed);
+        assert!(self.iter().rev().eq(elems.iter().rev()));
+        elems
+    }
+    fn push_back(&mut self, elem: i32) -> bool {
+        *self = Vector::push_back(self, elem);
+        true
+    }
+    fn pop_back(&mut self) -> Option<i32> {
+        let elem = self.get(self.len().checked_sub(1)?).copied();
+        *self = self.split_at(self.len() - 1).0;
+        elem
+    }
+    fn peek_back(&mut self) -> Option<i32> {
+        self.get(self.len().checked_sub(1)?).copied()
+    }
+    fn split_off(&mut self, at: usize) -> Self {
+        let (front, back) = self.split_at(at);
+        *self = front;
+        back
+    }
+    fn append(&mut self, other: &mut Self) {
+        *self = self.concat(&core::mem::take(other));
+    }
+    fn set(&mut self, at: usize, elem: i32) {
+        *self = Vector::set(self, at, elem);
+    }
+}
+
+const CAP: usize = 8;
+
+impl Subject for StaticList<i32, CAP> {
+    const CAPACITY: usize = CAP;
+
+    fn from_slice(elems: &[i32]) -> Self {
+        let mut list = StaticList::new();
+        for &elem in elems.iter().rev() {
+            list.push(elem).unwrap();
+        }
+        list
+    }
+    fn push_front(&mut self, elem: i32) -> bool {
+        self.push(elem).is_ok()
+    }
+    fn pop_front(&mut self) -> Option<i32> {
+        self.pop()
+    }
+    fn peek_front(&mut self) -> Option<i32> {
+        self.peek().copied()
+    }
+    fn contents(&mut self) -> Vec<i32> {
+        self.iter().copied().collect()
+    }
+}
+
+impl Subject for StaticDeque<i32, CAP> {
+    const BACK: bool = true;
+    const CAPACITY: usize = CAP;
+
+    fn from_slice(elems: &[i32]) -> Self {
+        let mut list = StaticDeque::new();
+        for &elem in elems {
+            list.push_back(elem).unwrap();
+        }
+        list
+    }
+    fn push_front(&mut self, elem: i32) -> bool {
+        StaticDeque::push_front(self, elem).is_ok()
+    }
+    fn push_back(&mut self, elem: i32) -> bool {
+        StaticDeque::push_back(self, elem).is_ok()
+    }
+    fn pop_front(&mut self) -> Option<i32> {
+        StaticDeque::pop_front(self)
+    }
+    fn pop_back(&mut self) -> Option<i32> {
+        StaticDeque::pop_back(self)
+    }
+    fn peek_front(&mut self) -> Option<i32> {
+        StaticDeque::peek_front(self).copied()
+    }
+    fn peek_back(&mut self) -> Option<i32> {
+        StaticDeque::peek_back(self).copied()
+    }
+    fn contents(&mut self) -> Vec<i32> {
+        self.iter().copied().collect()
+    }
+}
+
+proptest! {
+    #[test]
+    fn sll_matches_model(ops in ops::<sll::List<i32>>()) {
+        check::<sll::List<i32>>(&ops)?;
+    }
+
+    #[test]
+    fn deque_matches_model(ops in ops::<deque::List<i32>>()) {
+        check::<deque::List<i32>>(&ops)?;
+    }
+
+    #[test]
+    fn deque_book_matches_model(ops in ops::<deque_book::List<i32>>()) {
+        check::<deque_book::List<i32>>(&ops)?;
+    }
+
+    #[test]
+    fn shared_list_matches_model(ops in ops::<shared_list::List<i32>>()) {
+        check::<shared_list::List<i32>>(&ops)?;
+    }
+
+    #[test]
+    fn xor_list_matches_model(ops in ops::<XorList<i32>>()) {
+        check::<XorList<i32>>(&ops)?;
+    }
+
+    #[test]
+    fn static_list_matches_model(ops in ops::<StaticList<i32, CAP>>()) {
+        check::<StaticList<i32, CAP>>(&ops)?;
+    }
+
+    #[test]
+    fn static_deque_matches_model(ops in ops::<StaticDeque<i32, CAP>>()) {
+        check::<StaticDeque<i32, CAP>>(&ops)?;
+    }
+
+    #[test]
+    fn ra_list_matches_model(ops in ops::<ra_list::List<i32>>()) {
+        check::<ra_list::List<i32>>(&ops)?;
+    }
+
+    #[test]
+    fn vector_matches_model(ops in ops::<Vector<i32>>()) {
+        check::<Vector<i32>>(&ops)?;
+    }
+
+    #[test]
+    fn vector_set_persists(
+        elems in vec(any::<i32>(), 1..64),
+        sets in vec((any::<usize>(), any::<i32>()), 0..32),
+    ) {
+        set_persists::<Vector<i32>>(&elems, &sets)?;
+    }
+
+    #[test]
+    fn ra_list_set_persists(
+        elems in vec(any::<i32>(), 1..64),
+        sets in vec((any::<usize>(), any::<i32>()), 0..32),
+    ) {
+        set_persists::<ra_list::List<i32>>(&elems, &sets)?;
+    }
+
+    // Every version of a shared_list stays intact however later versions
+    // built from it are changed.
+    #[test]
+    fn shared_list_versions_persist(ops in ops::<shared_list::List<i32>>()) {
+        let mut list = shared_list::List::new();
+        let mut model = VecDeque::new();
+        let mut versions = Vec::new();
+        for op in &ops {
+            match op {
+                Op::PushFront(elem) => {
+                    Subject::push_front(&mut list, *elem);
+                    model.push_front(*elem);
+                }
+                Op::PopFront => {
+                    Subject::pop_front(&mut list);
+                    model.pop_front();
+                }
+                _ => continue,
+            }
+            versions.push((list.clone(), Vec::from(model.clone())));
+        }
+        for (mut version, expected) in versions {
+            prop_assert_eq!(version.contents(), expected);
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr;
+
+use crate::atomic::{AtomicPtr, AtomicUsize, Ordering::SeqCst};
+use crate::reclaim::Reclaimer;
+
+// Michael-Scott lock-free queue. `head` always points at a dummy node; the
+// first real element lives in `head.next`. Popping swings `head` forward and
+// the old dummy is retired, so the popped node becomes the new dummy.
+pub struct Queue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    len: AtomicUsize,
+    reclaim: Reclaimer,
+    _owns: PhantomData<T>,
+}
+
+struct Node<T> {
+    // Uninitialized in the dummy, and in any node whose element was popped.
+    elem: MaybeUninit<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn alloc(elem: MaybeUninit<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node {
+            elem,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        let dummy = Node::alloc(MaybeUninit::uninit());
+        Queue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            len: AtomicUsize::new(0),
+            reclaim: Reclaimer::new(),
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn push(&self, elem: T) {
+        let new_node = Node::alloc(MaybeUninit::new(elem));
+        // Count first so that len() can never underflow when a pop overtakes us.
+        self.len.fetch_add(1, SeqCst);
+
+        let _guard = self.reclaim.enter();
+        loop {
+            let tail = self.tail.load(SeqCst);
+            // SAFETY: the guard keeps tail alive even if it has been popped.
+            let next = unsafe { (*tail).next.load(SeqCst) };
+            if tail != self.tail.load(SeqCst) {
+                continue;
+            }
+            if next.is_null() {
+                let linked = unsafe { &(*tail).next }
+                    .compare_exchange(ptr::null_mut(), new_node, SeqCst, SeqCst)
+                    .is_ok();
+                if linked {
+                    let _ = self.tail.compare_exchange(tail, new_node, SeqCst, SeqCst);
+                    return;
+                }
+            } else {
+                // Tail is lagging behind; help it along.
+                let _ = self.tail.compare_exchange(tail, next, SeqCst, SeqCst);
+            }
+        }
+    }
+
+    pub fn try_pop(&self) -> Option<T> {
+        let guard = self.reclaim.enter();
+        loop {
+            let head = self.head.load(SeqCst);
+            let tail = self.tail.load(SeqCst);
+            let next = unsafe { (*head).next.load(SeqCst) };
+            if head != self.head.load(SeqCst) {
+                continue;
+            }
+            if head == tail {
+                if next.is_null() {
+                    return None;
+                }
+                let _ = self.tail.compare_exchange(tail, next, SeqCst, SeqCst);
+            } else if self.head.compare_exchange(head, next, SeqCst, SeqCst).is_ok() {
+                // SAFETY: winning the CAS gives us the element in `next`, which
+                // is now the dummy and will never be read for its element again.
+                let elem = unsafe { ptr::read((*next).elem.as_ptr()) };
+                unsafe { guard.retire(head) };
+                self.len.fetch_sub(1, SeqCst);
+                return Some(elem);
+            }
+        }
+    }
+
+    // Number of elements, counting pushes that are still in flight. Only
+    // exact when no other thread is touching the queue.
+    pub fn len(&self) -> usize {
+        self.len.load(SeqCst)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        self.free_nodes();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        self.free_nodes();
+    }
+}
+
+impl<T> Queue<T> {
+    fn free_nodes(&mut self) {
+        let dummy = unsafe { Box::from_raw(self.head.load(SeqCst)) };
+        let mut rest = Chain(dummy.next.load(SeqCst));
+        rest.free();
+    }
+}
+
+// The nodes after the dummy. If an element's destructor panics while `free`
+// runs, dropping the Chain during the unwind frees the rest.
+struct Chain<T>(*mut Node<T>);
+
+impl<T> Chain<T> {
+    fn free(&mut self) {
+        while !self.0.is_null() {
+            let mut node = unsafe { Box::from_raw(self.0) };
+            self.0 = node.next.load(SeqCst);
+            unsafe { ptr::drop_in_place(node.elem.as_mut_ptr()) };
+        }
+    }
+}
+
+impl<T> Drop for Chain<T> {
+    fn drop(&mut self) {
+        self.free();
+    }
+}
+
+#[cfg(all(test, not(feature = "loom")))]
+mod test {
+    use super::Queue;
+    use crate::panicky::{panics, Bomb};
+    use core::cell::Cell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn basics() {
+        let queue = Queue::new();
+        assert_eq!(queue.try_pop(), None);
+        assert!(queue.is_empty());
+
+        queue.push(1);
+        queue.push(2);
+        queue.push(3);
+        assert_eq!(queue.len(), 3);
+
+        assert_eq!(queue.try_pop(), Some(1));
+        assert_eq!(queue.try_pop(), Some(2));
+
+        queue.push(4);
+        queue.push(5);
+
+        assert_eq!(queue.try_pop(), Some(3));
+        assert_eq!(queue.try_pop(), Some(4));
+        assert_eq!(queue.try_pop(), Some(5));
+        assert_eq!(queue.try_pop(), None);
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn mpmc_hammer() {
+        const PRODUCERS: usize = 4;
+        const CONSUMERS: usize = 4;
+        const PER_PRODUCER: usize = 20_000;
+
+        let queue = Arc::new(Queue::new());
+        let popped = Arc::new(AtomicUsize::new(0));
+
+        let producers: Vec<_> = (0..PRODUCERS)
+            .map(|p| {
+                let queue = queue.clone();
+                thread::spawn(move || {
+                    for i in 0..PER_PRODUCER {
+                        queue.push((p, i));
+                    }
+                })
+            })
+            .collect();
+
+        let consumers: Vec<_> = (0..CONSUMERS)
+            .map(|_| {
+                let queue = queue.clone();
+                let popped = popped.clone();
+                thread::spawn(move || {
+                    // Each consumer must see every producer's items in order.
+                    let mut last = [None; PRODUCERS];
+                    while popped.load(Ordering::Relaxed) < PRODUCERS * PER_PRODUCER {
+                        if let Some((p, i)) = queue.try_pop() {
+                            assert!(last[p] < Some(i));
+                            last[p] = Some(i);
+                            popped.fetch_add(1, Ordering::Relaxed);
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for h in producers.into_iter().chain(consumers) {
+            h.join().unwrap();
+        }
+        assert_eq!(queue.try_pop(), None);
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn drops_everything() {
+        let elem = Arc::new(());
+        let queue = Queue::new();
+        for _ in 0..100 {
+            queue.push(elem.clone());
+        }
+        for _ in 0..40 {
+            queue.try_pop();
+        }
+        assert_eq!(Arc::strong_count(&elem), 61);
+        drop(queue);
+        assert_eq!(Arc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn drop_survives_panicking_destructor() {
+        let drops = Cell::new(0);
+        let queue = Queue::new();
+        for i in 0..5 {
+            queue.push(if i == 2 { Bomb::armed(&drops) } else { Bomb::new(&drops) });
+        }
+        assert!(panics(move || drop(queue)));
+        assert_eq!(drops.get(), 5);
+    }
+}
+
+#[cfg(all(test, feature = "loom"))]
+mod loom_test {
+    use super::Queue;
+    use loom::sync::Arc;
+    use loom::thread;
+
+    #[test]
+    fn push_pop_race() {
+        loom::model(|| {
+            let queue = Arc::new(Queue::new());
+            let q1 = queue.clone();
+            let q2 = queue.clone();
+
+            let t1 = thread::spawn(move || {
+                q1.push(1);
+                q1.push(2);
+            });
+            let t2 = thread::spawn(move || q2.try_pop());
+
+            t1.join().unwrap();
+            let first = t2.join().unwrap();
+
+            let mut rest = vec![];
+            while let Some(n) = queue.try_pop() {
+                rest.push(n);
+            }
+            match first {
+                Some(1) => assert_eq!(rest, vec![2]),
+                None => assert_eq!(rest, vec![1, 2]),
+                other => panic!("unexpected pop {:?}", other),
+            }
+        });
+    }
+
+    #[test]
+    fn concurrent_pushes() {
+        loom::model(|| {
+            let queue = Arc::new(Queue::new());
+            let q1 = queue.clone();
+            let q2 = queue.clone();
+
+            let t1 = thread::spawn(move || q1.push(1));
+            let t2 = thread::spawn(move || q2.push(2));
+            t1.join().unwrap();
+            t2.join().unwrap();
+
+            let mut got = vec![queue.try_pop().unwrap(), queue.try_pop().unwrap()];
+            got.sort();
+            assert_eq!(got, vec![1, 2]);
+            assert_eq!(queue.try_pop(), None);
+        });
+    }
+}
@@ -0,0 +1,69 @@
+// Element types that panic at awkward moments, for the panic-safety tests.
+
+use core::cell::Cell;
+use core::cmp::Ordering;
+use std::panic::{self, AssertUnwindSafe};
+
+// Counts its drops and, if armed, panics while being dropped.
+pub(crate) struct Bomb<'a> {
+    drops: &'a Cell<usize>,
+    armed: bool,
+}
+
+impl<'a> Bomb<'a> {
+    pub(crate) fn new(drops: &'a Cell<usize>) -> Self {
+        Bomb {
+            drops,
+            armed: false,
+        }
+    }
+
+    pub(crate) fn armed(drops: &'a Cell<usize>) -> Self {
+        Bomb {
+            drops,
+            armed: true,
+        }
+    }
+}
+
+impl Drop for Bomb<'_> {
+    fn drop(&mut self) {
+        self.drops.set(self.drops.get() + 1);
+        if self.armed {
+            panic!("bomb went off");
+        }
+    }
+}
+
+// Orders like the number it wraps, but panics when asked to compare a
+// negative one.
+#[derive(Debug)]
+pub(crate) struct Touchy(pub(crate) i32);
+
+impl Ord for Touchy {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.0 < 0 || other.0 < 0 {
+            panic!("touchy comparison");
+        }
+        self.0.cmp(&other.0)
+    }
+}
+
+impl PartialOrd for Touchy {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Touchy {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Touchy {}
+
+// Runs `f`, reporting whether it panicked.
+pub(crate) fn panics<R>(f: impl FnOnce() -> R) -> bool {
+    panic::catch_unwind(AssertUnwindSafe(f)).is_err()
+}
@@ -0,0 +1,315 @@
+// rayon support. Parallel iterators over the lists count their nodes once
+// up front and split by walking to the midpoint. The borrowing ones just
+// hand each half a position to start from; the owning ones cut the list in
+// two there, so each half owns its nodes and frees them wherever it ends up.
+
+use alloc::vec::Vec;
+use rayon::iter::plumbing::{Folder, Producer};
+use rayon::prelude::*;
+
+use crate::{deque_book, shared_list, sll};
+
+// A shared position in a singly linked list that can be handed to another
+// thread.
+pub(crate) trait Cursor: Copy + Send {
+    type Item: Send;
+    // The element at this node and the node after it.
+    fn step(self) -> (Self::Item, Option<Self>);
+}
+
+// `len` nodes starting at `head`.
+pub(crate) struct Nodes<C> {
+    head: Option<C>,
+    len: usize,
+}
+
+impl<C> Nodes<C> {
+    pub(crate) fn new(head: Option<C>, len: usize) -> Self {
+        Nodes { head, len }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<C: Cursor> Producer for Nodes<C> {
+    type Item = C::Item;
+    // Only adaptors that walk from the back (rev, zip and the like) get
+    // here, which a singly linked list can't do, so gather the leaf first.
+    type IntoIter = alloc::vec::IntoIter<C::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut elems = Vec::with_capacity(self.len);
+        let mut cur = self.head;
+        for _ in 0..self.len {
+            let (elem, next) = cur.expect("fewer nodes than counted").step();
+            elems.push(elem);
+            cur = next;
+        }
+        elems.into_iter()
+    }
+
+    // Each split walks to `index`, so splitting costs a pass over the nodes
+    // per level, spread over the threads doing the splitting.
+    fn split_at(self, index: usize) -> (Self, Self) {
+        let mut right = self.head;
+        for _ in 0..index {
+            right = right.and_then(|cur| cur.step().1);
+        }
+        (
+            Nodes::new(self.head, index),
+            Nodes::new(right, self.len - index),
+        )
+    }
+
+    fn fold_with<F: Folder<Self::Item>>(self, mut folder: F) -> F {
+        let mut cur = self.head;
+        for _ in 0..self.len {
+            if folder.full() {
+                break;
+            }
+            let (elem, next) = cur.expect("fewer nodes than counted").step();
+            folder = folder.consume(elem);
+            cur = next;
+        }
+        folder
+    }
+}
+
+// A list, or a piece cut off one, that owns its nodes outright and can be
+// handed to another thread.
+pub(crate) trait Detach: Send + Sized {
+    type Item: Send;
+    // Keeps the first `at` nodes and returns the rest.
+    fn detach(&mut self, at: usize) -> Self;
+    fn pop_front(&mut self) -> Option<Self::Item>;
+}
+
+// The `len` nodes of `list`.
+pub(crate) struct Owned<L> {
+    list: L,
+    len: usize,
+}
+
+impl<L> Owned<L> {
+    pub(crate) fn new(list: L, len: usize) -> Self {
+        Owned { list, len }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<L: Detach> Producer for Owned<L> {
+    type Item = L::Item;
+    // As for Nodes.
+    type IntoIter = alloc::vec::IntoIter<L::Item>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        let mut elems = Vec::with_capacity(self.len);
+        for _ in 0..self.len {
+            elems.push(self.list.pop_front().expect("fewer nodes than counted"));
+        }
+        elems.into_iter()
+    }
+
+    // Walks to `index` and cuts the list there, like Nodes.
+    fn split_at(mut self, index: usize) -> (Self, Self) {
+        let rest = self.list.detach(index);
+        (
+            Owned::new(self.list, index),
+            Owned::new(rest, self.len - index),
+        )
+    }
+
+    // Whatever's left once the folder is full is dropped with the list.
+    fn fold_with<F: Folder<Self::Item>>(mut self, mut folder: F) -> F {
+        for _ in 0..self.len {
+            if folder.full() {
+                break;
+            }
+            folder = folder.consume(self.list.pop_front().expect("fewer nodes than counted"));
+        }
+        folder
+    }
+}
+
+/********** Collecting **********/
+
+// The elements are gathered into a Vec in parallel, in order, and linked up
+// on the calling thread.
+
+impl<T: Send> FromParallelIterator<T> for sll::List<T> {
+    fn from_par_iter<I: IntoParallelIterator<Item = T>>(par_iter: I) -> Self {
+        let mut list = sll::List::new();
+        list.par_extend(par_iter);
+        list
+    }
+}
+
+// Adds the elements at the end of the list, in iteration order.
+impl<T: Send> ParallelExtend<T> for sll::List<T> {
+    fn par_extend<I: IntoParallelIterator<Item = T>>(&mut self, par_iter: I) {
+        let elems: Vec<T> = par_iter.into_par_iter().collect();
+        let mut tail = sll::List::new();
+        for elem in elems.into_iter().rev() {
+            tail.push(elem);
+        }
+        self.append(&mut tail);
+    }
+}
+
+impl<T: Send> FromParallelIterator<T> for deque_book::List<T> {
+    fn from_par_iter<I: IntoParallelIterator<Item = T>>(par_iter: I) -> Self {
+        let mut list = deque_book::List::new();
+        list.par_extend(par_iter);
+        list
+    }
+}
+
+impl<T: Send> ParallelExtend<T> for deque_book::List<T> {
+    fn par_extend<I: IntoParallelIterator<Item = T>>(&mut self, par_iter: I) {
+        let elems: Vec<T> = par_iter.into_par_iter().collect();
+        for elem in elems {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T: Send> FromParallelIterator<T> for shared_list::List<T> {
+    fn from_par_iter<I: IntoParallelIterator<Item = T>>(par_iter: I) -> Self {
+        let elems: Vec<T> = par_iter.into_par_iter().collect();
+        elems
+            .into_iter()
+            .rev()
+            .fold(shared_list::List::new(), |list, elem| list.append(elem))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::allocator::{AllocError, Allocator, Global};
+    use core::alloc::Layout;
+    use core::ptr::NonNull;
+    use core::sync::atomic::AtomicUsize;
+    use core::sync::atomic::Ordering::Relaxed;
+    use std::vec::Vec;
+
+    // Long enough that rayon actually splits.
+    const N: i64 = 10_000;
+
+    fn sll_of(n: i64) -> sll::List<i64> {
+        (0..n).rev().fold(sll::List::new(), |mut list, i| {
+            list.push(i);
+            list
+        })
+    }
+
+    #[test]
+    fn sll_par_iter_matches_iter() {
+        let list = sll_of(N);
+        let seq: Vec<i64> = list.iter().map(|x| x * 3).collect();
+        let par: Vec<i64> = list.par_iter().map(|x| x * 3).collect();
+        assert_eq!(par, seq);
+        assert_eq!(list.par_iter().len(), N as usize);
+        assert_eq!(list.par_iter().sum::<i64>(), list.iter().sum::<i64>());
+
+        // These go through the gathered leaves.
+        let rev: Vec<i64> = list.par_iter().rev().copied().collect();
+        assert!(rev.iter().eq(list.iter().collect::<Vec<_>>().into_iter().rev()));
+        let zipped: Vec<(usize, i64)> = list.par_iter().copied().enumerate().collect();
+        assert!(zipped.iter().map(|&(i, x)| i as i64 - x).all(|d| d == 0));
+
+        assert_eq!(list.par_iter().find_first(|&&x| x > 5000), Some(&5001));
+        assert_eq!(sll::List::<i64>::new().par_iter().count(), 0);
+    }
+
+    #[test]
+    fn sll_into_par_iter_matches_into_iter() {
+        let seq: Vec<i64> = sll_of(N).into_iter().collect();
+        let par: Vec<i64> = sll_of(N).into_par_iter().collect();
+        assert_eq!(par, seq);
+    }
+
+    #[test]
+    fn shared_list_par_iter_matches_iter() {
+        let list: shared_list::List<i64> = (0..N).into_par_iter().collect();
+        assert!(list.iter().copied().eq(0..N));
+
+        // Versions that share nodes can be iterated at the same time.
+        let tail = list.tail().tail();
+        let (whole, rest) = (list.par_iter(), tail.par_iter());
+        let (a, b): (Vec<i64>, Vec<i64>) = rayon::join(
+            move || whole.map(|x| x + 1).collect(),
+            move || rest.map(|x| x + 1).collect(),
+        );
+        assert!(a.into_iter().eq(1..N + 1));
+        assert!(b.into_iter().eq(3..N + 1));
+    }
+
+    #[test]
+    fn deque_book_into_par_iter_matches_into_iter() {
+        let list: deque_book::List<i64> = (0..N).into_par_iter().collect();
+        let seq: Vec<i64> = list.clone().into_iter().collect();
+        let par: Vec<i64> = list.into_par_iter().map(|x| x).collect();
+        assert_eq!(par, seq);
+        assert!(seq.into_iter().eq(0..N));
+
+        let list: deque_book::List<i64> = (0..N).into_par_iter().collect();
+        assert_eq!(list.into_par_iter().len(), N as usize);
+        let list: deque_book::List<i64> = (0..N).into_par_iter().collect();
+        let rev: Vec<i64> = list.into_par_iter().rev().collect();
+        assert!(rev.into_iter().eq((0..N).rev()));
+    }
+
+    // Forwards to Global and counts live blocks, from any thread.
+    #[derive(Default)]
+    struct Tally(AtomicUsize);
+
+    unsafe impl Allocator for Tally {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+            self.0.fetch_add(1, Relaxed);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.fetch_sub(1, Relaxed);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn into_par_iter_with_an_allocator() {
+        let tally = Tally::default();
+        let mut list = sll::List::new_in(&tally);
+        for i in (0..N).rev() {
+            list.push(i);
+        }
+        let par: Vec<i64> = list.into_par_iter().map(|x| x * 2).collect();
+        assert!(par.into_iter().eq((0..N).map(|x| x * 2)));
+        assert_eq!(tally.0.load(Relaxed), 0);
+
+        let mut list = deque_book::List::with_pool_in(8, &tally);
+        for i in 0..N {
+            list.push_back(i);
+        }
+        // Stops early; the halves that never ran free their own nodes.
+        assert_eq!(list.into_par_iter().find_first(|&x| x > 100), Some(101));
+        assert_eq!(tally.0.load(Relaxed), 0);
+    }
+
+    #[test]
+    fn par_extend_appends_in_order() {
+        let mut list = sll_of(3);
+        list.par_extend((3..N).into_par_iter());
+        assert!(list.iter().copied().eq(0..N));
+
+        let mut list: deque_book::List<i64> = (0..3).into_par_iter().collect();
+        list.par_extend((3..N).into_par_iter().filter(|x| x % 2 == 0));
+        let expected = (0..3).chain((3..N).filter(|x| x % 2 == 0));
+        assert!(list.into_iter().eq(expected));
+    }
+}
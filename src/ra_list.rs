@@ -0,0 +1,386 @@
+// Okasaki's skew-binary random-access list: a persistent list like
+// shared_list, sharing structure through Rc the same way, but fast to index.
+//
+// The elements are kept as a list of complete binary trees, each holding
+// 2^k - 1 elements in preorder, with sizes increasing along the list except
+// that the first two may be equal. cons either starts a new one-element tree
+// or, when the first two trees are the same size, joins them under the new
+// element, so it's O(1); tail undoes that. Indexing skips whole trees and
+// then descends one, O(log n) both ways, and set copies just that path.
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+
+pub struct List<T> {
+    head: Link<T>,
+    len: usize,
+}
+
+type Link<T> = Option<Rc<Digit<T>>>;
+
+// A tree and how many elements it holds.
+struct Digit<T> {
+    size: usize,
+    tree: Rc<Tree<T>>,
+    next: Link<T>,
+}
+
+struct Tree<T> {
+    elem: T,
+    kids: Option<Kids<T>>,
+}
+
+// Left and right subtrees, each half of what's left after the root.
+type Kids<T> = (Rc<Tree<T>>, Rc<Tree<T>>);
+
+impl<T> List<T> {
+    pub fn new() -> Self {
+        List { head: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // A new version with `elem` in front; this one is unchanged.
+    pub fn cons(&self, elem: T) -> List<T> {
+        let len = self.len + 1;
+        if let Some(first) = &self.head {
+            if let Some(second) = &first.next {
+                if first.size == second.size {
+                    let tree = Tree {
+                        elem,
+                        kids: Some((first.tree.clone(), second.tree.clone())),
+                    };
+                    let digit = Digit {
+                        size: 2 * first.size + 1,
+                        tree: Rc::new(tree),
+                        next: second.next.clone(),
+                    };
+                    return List {
+                        head: Some(Rc::new(digit)),
+                        len,
+                    };
+                }
+            }
+        }
+        let digit = Digit {
+            size: 1,
+            tree: Rc::new(Tree { elem, kids: None }),
+            next: self.head.clone(),
+        };
+        List {
+            head: Some(Rc::new(digit)),
+            len,
+        }
+    }
+
+    pub fn head(&self) -> Option<&T> {
+        self.head.as_ref().map(|digit| &digit.tree.elem)
+    }
+
+    // Everything after the head; empty if this is.
+    pub fn tail(&self) -> List<T> {
+        let first = match &self.head {
+            Some(first) => first,
+            None => return List::new(),
+        };
+        let head = match &first.tree.kids {
+            None => first.next.clone(),
+            Some((left, right)) => {
+                let size = first.size / 2;
+                let right = Digit {
+                    size,
+                    tree: right.clone(),
+                    next: first.next.clone(),
+                };
+                let left = Digit {
+                    size,
+                    tree: left.clone(),
+                    next: Some(Rc::new(right)),
+                };
+                Some(Rc::new(left))
+            }
+        };
+        List {
+            head,
+            len: self.len - 1,
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let mut index = index;
+        let mut digit = self.head.as_deref();
+        while let Some(d) = digit {
+            if index < d.size {
+                return Some(d.tree.get(d.size, index));
+            }
+            index -= d.size;
+            digit = d.next.as_deref();
+        }
+        None
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            digit: self.head.as_deref(),
+            stack: Vec::new(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Clone> List<T> {
+    // A new version with the element at `index` replaced; this one is
+    // unchanged. Copies the trees in front of it and the path down to it,
+    // cloning the elements along that path, and shares the rest.
+    //
+    // Panics if `index` is out of bounds.
+    pub fn set(&self, index: usize, elem: T) -> List<T> {
+        assert!(index < self.len, "set index out of bounds");
+        let head = self.head.as_ref().unwrap().set(index, elem);
+        List {
+            head: Some(Rc::new(head)),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Clone> Digit<T> {
+    fn set(&self, index: usize, elem: T) -> Digit<T> {
+        if index < self.size {
+            Digit {
+                size: self.size,
+                tree: Rc::new(self.tree.set(self.size, index, elem)),
+                next: self.next.clone(),
+            }
+        } else {
+            let next = self.next.as_ref().unwrap();
+            Digit {
+                size: self.size,
+                tree: self.tree.clone(),
+                next: Some(Rc::new(next.set(index - self.size, elem))),
+            }
+        }
+    }
+}
+
+impl<T> Tree<T> {
+    // The element at `index`, in preorder, of this tree of `size` elements.
+    fn get(&self, size: usize, index: usize) -> &T {
+        let (mut tree, mut size, mut index) = (self, size, index);
+        while index != 0 {
+            let (left, right) = tree.kids.as_ref().unwrap();
+            size /= 2;
+            if index <= size {
+                tree = left;
+                index -= 1;
+            } else {
+                tree = right;
+                index -= 1 + size;
+            }
+        }
+        &tree.elem
+    }
+}
+
+impl<T: Clone> Tree<T> {
+    fn set(&self, size: usize, index: usize, elem: T) -> Tree<T> {
+        let (left, right) = match &self.kids {
+            _ if index == 0 => {
+                return Tree {
+                    elem,
+                    kids: self.kids.clone(),
+                }
+            }
+            Some(kids) => kids,
+            None => unreachable!(),
+        };
+        let half = size / 2;
+        let kids = if index <= half {
+            (Rc::new(left.set(half, index - 1, elem)), right.clone())
+        } else {
+            (left.clone(), Rc::new(right.set(half, index - 1 - half, elem)))
+        };
+        Tree {
+            elem: self.elem.clone(),
+            kids: Some(kids),
+        }
+    }
+}
+
+// Cloning a version only bumps the first tree's refcount.
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: PartialEq> PartialEq for List<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for List<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for List<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// The first element of the iterator ends up at the head.
+impl<T> FromIterator<T> for List<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elems: Vec<T> = iter.into_iter().collect();
+        elems
+            .into_iter()
+            .rev()
+            .fold(List::new(), |list, elem| list.cons(elem))
+    }
+}
+
+/********** Iterator **********/
+
+// Goes through each tree in preorder, which is index order.
+pub struct Iter<'a, T> {
+    digit: Option<&'a Digit<T>>,
+    // Subtrees of the current tree still to visit, next one on top.
+    stack: Vec<&'a Tree<T>>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let tree = match self.stack.pop() {
+            Some(tree) => tree,
+            None => {
+                let digit = self.digit?;
+                self.digit = digit.next.as_deref();
+                &*digit.tree
+            }
+        };
+        if let Some((left, right)) = &tree.kids {
+            self.stack.push(right);
+            self.stack.push(left);
+        }
+        self.len -= 1;
+        Some(&tree.elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod test {
+    use super::List;
+    use std::vec::Vec;
+
+    // The tree sizes, front to back.
+    fn shape<T>(list: &List<T>) -> Vec<usize> {
+        let mut sizes = Vec::new();
+        let mut digit = list.head.as_deref();
+        while let Some(d) = digit {
+            sizes.push(d.size);
+            digit = d.next.as_deref();
+        }
+        sizes
+    }
+
+    #[test]
+    fn basics() {
+        let list = List::new();
+        assert_eq!(list.head(), None);
+        assert!(list.tail().is_empty());
+        assert_eq!(list.get(0), None);
+
+        let list = list.cons(3).cons(2).cons(1);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.head(), Some(&1));
+        assert_eq!(list.tail().head(), Some(&2));
+        assert_eq!(list.tail().tail().tail().head(), None);
+        assert_eq!(format!("{:?}", list), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn skew_binary_shape() {
+        let mut list = List::new();
+        let mut shapes = Vec::new();
+        for i in 0..7 {
+            list = list.cons(i);
+            shapes.push(shape(&list));
+        }
+        assert_eq!(
+            shapes,
+            [
+                vec![1],
+                vec![1, 1],
+                vec![3],
+                vec![1, 3],
+                vec![1, 1, 3],
+                vec![3, 3],
+                vec![7],
+            ]
+        );
+        // tail undoes cons.
+        for expected in shapes.iter().rev().skip(1) {
+            list = list.tail();
+            assert_eq!(&shape(&list), expected);
+        }
+    }
+
+    #[test]
+    fn get_set_and_iter() {
+        let n = 1000;
+        let list: List<usize> = (0..n).collect();
+        assert_eq!(list.len(), n);
+        assert!(list.iter().copied().eq(0..n));
+        assert_eq!(list.iter().len(), n);
+        for i in 0..n {
+            assert_eq!(list.get(i), Some(&i));
+        }
+        assert_eq!(list.get(n), None);
+
+        let mut version = list.clone();
+        for i in (0..n).step_by(7) {
+            version = version.set(i, i * 10);
+        }
+        for i in 0..n {
+            let expected = if i % 7 == 0 { i * 10 } else { i };
+            assert_eq!(version.get(i), Some(&expected));
+            // The original is untouched.
+            assert_eq!(list.get(i), Some(&i));
+        }
+        assert_ne!(version, list);
+        assert_eq!(list, (0..n).collect());
+    }
+
+    #[test]
+    #[should_panic(expected = "set index out of bounds")]
+    fn set_out_of_bounds() {
+        let list: List<i32> = (0..5).collect();
+        list.set(5, 0);
+    }
+}
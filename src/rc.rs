@@ -0,0 +1,333 @@
+use core::alloc::Layout;
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::{self, ManuallyDrop};
+use core::ops::Deref;
+use core::ptr::{self, NonNull};
+
+use crate::allocator::{self, AllocError, Allocator, Global};
+
+// Just the parts of std::rc::Rc that deque_book needs, but allocated through
+// an `Allocator`. Each handle carries its own copy of the allocator, which is
+// free for Global and a single pointer for `&A`.
+pub(crate) struct Rc<T, A: Allocator + Clone = Global> {
+    ptr: NonNull<RcBox<T>>,
+    alloc: A,
+    _owns: PhantomData<RcBox<T>>,
+}
+
+struct RcBox<T> {
+    strong: Cell<usize>,
+    value: T,
+}
+
+impl<T, A: Allocator + Clone> Rc<T, A> {
+    pub(crate) fn new_in(value: T, alloc: A) -> Self {
+        let ptr = allocator::alloc_in(
+            RcBox {
+                strong: Cell::new(1),
+                value,
+            },
+            &alloc,
+        );
+        Rc {
+            ptr,
+            alloc,
+            _owns: PhantomData,
+        }
+    }
+
+    pub(crate) fn try_new_in(value: T, alloc: A) -> Result<Self, (T, AllocError)> {
+        match allocator::try_alloc_in(
+            RcBox {
+                strong: Cell::new(1),
+                value,
+            },
+            &alloc,
+        ) {
+            Ok(ptr) => Ok(Rc {
+                ptr,
+                alloc,
+                _owns: PhantomData,
+            }),
+            Err((rc_box, err)) => Err((rc_box.value, err)),
+        }
+    }
+
+    fn inner(&self) -> &RcBox<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    pub(crate) fn strong_count(this: &Self) -> usize {
+        this.inner().strong.get()
+    }
+
+    pub(crate) fn try_unwrap(this: Self) -> Result<T, Self> {
+        if this.inner().strong.get() != 1 {
+            return Err(this);
+        }
+        let this = ManuallyDrop::new(this);
+        // SAFETY: we hold the only handle, and `this` is never dropped.
+        unsafe {
+            let alloc = ptr::read(&this.alloc);
+            Ok(allocator::take_in(this.ptr, &alloc).value)
+        }
+    }
+
+    // Like try_unwrap, but keeps the allocation around as an RcSlot so it can
+    // be reused for another value.
+    pub(crate) fn try_unwrap_slot(this: Self) -> Result<(T, RcSlot<T, A>), Self> {
+        if this.inner().strong.get() != 1 {
+            return Err(this);
+        }
+        let this = ManuallyDrop::new(this);
+        unsafe {
+            let value = ptr::read(&(*this.ptr.as_ptr()).value);
+            let slot = RcSlot {
+                ptr: this.ptr,
+                alloc: ptr::read(&this.alloc),
+            };
+            Ok((value, slot))
+        }
+    }
+}
+
+// The allocation behind an Rc whose value has been moved out.
+pub(crate) struct RcSlot<T, A: Allocator + Clone = Global> {
+    ptr: NonNull<RcBox<T>>,
+    alloc: A,
+}
+
+impl<T, A: Allocator + Clone> RcSlot<T, A> {
+    pub(crate) fn fill(self, value: T) -> Rc<T, A> {
+        let slot = ManuallyDrop::new(self);
+        unsafe {
+            slot.ptr.as_ptr().write(RcBox {
+                strong: Cell::new(1),
+                value,
+            });
+            Rc {
+                ptr: slot.ptr,
+                alloc: ptr::read(&slot.alloc),
+                _owns: PhantomData,
+            }
+        }
+    }
+}
+
+// RcSlot holds no value, and Rc only drops its value, so T may dangle in
+// both; see lib.rs.
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T, A: Allocator + Clone> Drop for RcSlot<T, A> {
+    fn drop(&mut self) {
+        unsafe {
+            self.alloc
+                .deallocate(self.ptr.cast(), Layout::new::<RcBox<T>>())
+        };
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T, A: Allocator + Clone> Drop for RcSlot<T, A> {
+    fn drop(&mut self) {
+        // SAFETY: the value was already moved out; only the memory is left.
+        unsafe {
+            self.alloc
+                .deallocate(self.ptr.cast(), Layout::new::<RcBox<T>>())
+        };
+    }
+}
+
+// A stack of RcSlots threaded through their own memory: a free slot holds
+// the link to the one below it and the allocator it came from, where the
+// RcBox used to be. Keeping slots needs no memory besides theirs.
+pub(crate) struct SlotPool<T, A: Allocator + Clone = Global> {
+    top: Option<NonNull<RcBox<T>>>,
+    len: usize,
+    _owns: PhantomData<RcSlot<T, A>>,
+}
+
+struct Free<T, A> {
+    below: Option<NonNull<RcBox<T>>>,
+    alloc: A,
+}
+
+impl<T, A: Allocator + Clone> SlotPool<T, A> {
+    // Fixed by the types, so the check in push folds away.
+    const FITS: bool = mem::size_of::<Free<T, A>>() <= mem::size_of::<RcBox<T>>()
+        && mem::align_of::<Free<T, A>>() <= mem::align_of::<RcBox<T>>();
+
+    pub(crate) fn new() -> Self {
+        SlotPool {
+            top: None,
+            len: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    // Keeps `slot` for a later pop. If a free slot wouldn't fit in its
+    // memory, which only an allocator bigger than the value can cause, the
+    // slot goes straight back to its allocator instead and the pool stays
+    // empty.
+    pub(crate) fn push(&mut self, slot: RcSlot<T, A>) {
+        if !Self::FITS {
+            drop(slot);
+            return;
+        }
+        let slot = ManuallyDrop::new(slot);
+        // SAFETY: the slot's memory is unused and, checked above, big enough.
+        unsafe {
+            let free = Free {
+                below: self.top,
+                alloc: ptr::read(&slot.alloc),
+            };
+            slot.ptr.cast::<Free<T, A>>().as_ptr().write(free);
+        }
+        self.top = Some(slot.ptr);
+        self.len += 1;
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<RcSlot<T, A>> {
+        let ptr = self.top?;
+        // SAFETY: every slot on the stack holds a Free, written by push.
+        let Free { below, alloc } = unsafe { ptr.cast::<Free<T, A>>().as_ptr().read() };
+        self.top = below;
+        self.len -= 1;
+        Some(RcSlot { ptr, alloc })
+    }
+
+    // Hands every slot back to its allocator.
+    pub(crate) fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T, A: Allocator + Clone> Drop for SlotPool<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T, A: Allocator + Clone> Drop for SlotPool<T, A> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, A: Allocator + Clone> Clone for Rc<T, A> {
+    fn clone(&self) -> Self {
+        let strong = &self.inner().strong;
+        strong.set(strong.get() + 1);
+        Rc {
+            ptr: self.ptr,
+            alloc: self.alloc.clone(),
+            _owns: PhantomData,
+        }
+    }
+}
+
+impl<T, A: Allocator + Clone> Deref for Rc<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T, A: Allocator + Clone> Drop for Rc<T, A> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T, A: Allocator + Clone> Drop for Rc<T, A> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+impl<T, A: Allocator + Clone> Rc<T, A> {
+    fn release(&mut self) {
+        let strong = &self.inner().strong;
+        strong.set(strong.get() - 1);
+        if strong.get() == 0 {
+            unsafe { drop(allocator::take_in(self.ptr, &self.alloc)) };
+        }
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator + Clone> fmt::Debug for Rc<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Rc, SlotPool};
+    use crate::allocator::test::Counting;
+    use crate::allocator::{AllocError, Allocator};
+    use core::alloc::Layout;
+    use core::ptr::NonNull;
+
+    // An allocator held by value that's far bigger than a small RcBox.
+    #[derive(Clone)]
+    struct Bulky<'a> {
+        counting: &'a Counting,
+        _bulk: [u64; 4],
+    }
+
+    unsafe impl Allocator for Bulky<'_> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+            self.counting.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.counting.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn pool_keeps_slots_that_fit() {
+        let counting = Counting::default();
+        let mut pool = SlotPool::new();
+        let rc = Rc::new_in(1u64, &counting);
+        let (value, slot) = Rc::try_unwrap_slot(rc).ok().unwrap();
+        assert_eq!(value, 1);
+        pool.push(slot);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(counting.live(), 1);
+
+        let rc = pool.pop().unwrap().fill(2);
+        assert_eq!(*rc, 2);
+        assert_eq!(pool.len(), 0);
+        assert_eq!(counting.allocs.get(), 1);
+        drop(rc);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn pool_frees_slots_too_small_for_the_allocator() {
+        let counting = Counting::default();
+        let mut pool = SlotPool::new();
+        let bulky = Bulky {
+            counting: &counting,
+            _bulk: [0; 4],
+        };
+        let rc = Rc::new_in(1u8, bulky);
+        let (_, slot) = Rc::try_unwrap_slot(rc).ok().unwrap();
+        pool.push(slot);
+        assert_eq!(pool.len(), 0);
+        assert!(pool.pop().is_none());
+        assert_eq!(counting.live(), 0);
+    }
+}
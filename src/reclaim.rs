@@ -0,0 +1,121 @@
+use alloc::boxed::Box;
+use core::ptr;
+
+use crate::atomic::{AtomicPtr, AtomicUsize, Ordering::SeqCst};
+
+// Deferred reclamation for nodes that have been unlinked from a lock-free list
+// but may still be read by other threads.
+//
+// Every operation that dereferences shared nodes holds a `Guard`, which bumps
+// `active`. Unlinked nodes are not freed right away; they are chained onto
+// `retired`. When a guard is dropped by the last active thread, nothing can
+// still be looking at the retired nodes, so they are freed.
+//
+// This is the "threads in pop" scheme from C++ Concurrency in Action. Under
+// constant contention `active` may never reach zero and garbage piles up until
+// the next quiet moment or until the owning list is dropped.
+
+pub(crate) struct Reclaimer {
+    active: AtomicUsize,
+    retired: AtomicPtr<Retired>,
+}
+
+struct Retired {
+    ptr: *mut u8,
+    drop_fn: unsafe fn(*mut u8),
+    next: *mut Retired,
+}
+
+unsafe fn drop_boxed<N>(ptr: *mut u8) {
+    drop(Box::from_raw(ptr as *mut N));
+}
+
+pub(crate) struct Guard<'a> {
+    reclaimer: &'a Reclaimer,
+}
+
+impl Reclaimer {
+    pub(crate) fn new() -> Self {
+        Reclaimer {
+            active: AtomicUsize::new(0),
+            retired: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    pub(crate) fn enter(&self) -> Guard<'_> {
+        self.active.fetch_add(1, SeqCst);
+        Guard { reclaimer: self }
+    }
+
+    fn chain(&self, first: *mut Retired) {
+        if first.is_null() {
+            return;
+        }
+        let mut last = first;
+        // SAFETY: the chain was unlinked by us and nobody else can see it.
+        unsafe {
+            while !(*last).next.is_null() {
+                last = (*last).next;
+            }
+        }
+        let mut head = self.retired.load(SeqCst);
+        loop {
+            unsafe { (*last).next = head };
+            match self.retired.compare_exchange(head, first, SeqCst, SeqCst) {
+                Ok(_) => return,
+                Err(cur) => head = cur,
+            }
+        }
+    }
+
+    fn leave(&self) {
+        if self.active.load(SeqCst) == 1 {
+            let garbage = self.retired.swap(ptr::null_mut(), SeqCst);
+            if self.active.fetch_sub(1, SeqCst) == 1 {
+                // Everything in `garbage` was retired before the swap, and every
+                // thread that was active back then has since left.
+                unsafe { free_chain(garbage) };
+            } else {
+                self.chain(garbage);
+            }
+        } else {
+            self.active.fetch_sub(1, SeqCst);
+        }
+    }
+}
+
+impl Guard<'_> {
+    // Hands a node that is no longer reachable from the list over for
+    // reclamation. It is dropped as a `Box<N>` once no guard can observe it.
+    //
+    // SAFETY: `node` must come from `Box::into_raw`, must already be unlinked,
+    // and must not be retired twice.
+    pub(crate) unsafe fn retire<N>(&self, node: *mut N) {
+        let entry = Box::into_raw(Box::new(Retired {
+            ptr: node as *mut u8,
+            drop_fn: drop_boxed::<N>,
+            next: ptr::null_mut(),
+        }));
+        self.reclaimer.chain(entry);
+    }
+}
+
+impl Drop for Guard<'_> {
+    fn drop(&mut self) {
+        self.reclaimer.leave();
+    }
+}
+
+unsafe fn free_chain(mut cur: *mut Retired) {
+    while !cur.is_null() {
+        let entry = Box::from_raw(cur);
+        cur = entry.next;
+        (entry.drop_fn)(entry.ptr);
+    }
+}
+
+impl Drop for Reclaimer {
+    fn drop(&mut self) {
+        unsafe { free_chain(self.retired.load(SeqCst)) };
+    }
+}
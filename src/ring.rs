@@ -0,0 +1,413 @@
+use alloc::boxed::Box;
+use core::fmt;
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+use crate::allocator::{self, Global};
+
+// A circular doubly linked list seen through a cursor, `current`, that can
+// move forever in either direction. Global only: join and split move nodes
+// between rings, and every ring has to free them the same way.
+pub struct Ring<T> {
+    cur: Link<T>,
+    // Where the span that split() cuts out starts, if set.
+    mark: Link<T>,
+    _owns: PhantomData<Box<Node<T>>>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    elem: T,
+    next: NonNull<Node<T>>,
+    prev: NonNull<Node<T>>,
+}
+
+unsafe impl<T: Send> Send for Ring<T> {}
+unsafe impl<T: Sync> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    pub fn new() -> Self {
+        Ring {
+            cur: None,
+            mark: None,
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cur.is_none()
+    }
+
+    pub fn current(&self) -> Option<&T> {
+        self.cur.map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut T> {
+        self.cur.map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    pub fn advance(&mut self) {
+        self.cur = self.cur.map(|node| unsafe { (*node.as_ptr()).next });
+    }
+
+    pub fn retreat(&mut self) {
+        self.cur = self.cur.map(|node| unsafe { (*node.as_ptr()).prev });
+    }
+
+    // Takes n steps forward, which in a ring of k elements lands where
+    // n % k steps would.
+    pub fn advance_by(&mut self, n: usize) {
+        for _ in 0..n {
+            self.advance();
+        }
+    }
+
+    // Inserts `elem` just before the current element and makes it current,
+    // so remove_here undoes it.
+    pub fn insert_here(&mut self, elem: T) {
+        let node = self.new_node(elem);
+        if let Some(cur) = self.cur {
+            unsafe { link_before(cur, node) };
+        }
+        self.cur = Some(node);
+    }
+
+    // Removes the current element; the one after it becomes current.
+    pub fn remove_here(&mut self) -> Option<T> {
+        let node = self.cur?;
+        unsafe {
+            let Node { next, prev, .. } = *node.as_ptr();
+            if next == node {
+                self.cur = None;
+            } else {
+                (*prev.as_ptr()).next = next;
+                (*next.as_ptr()).prev = prev;
+                self.cur = Some(next);
+            }
+            if self.mark == Some(node) {
+                self.mark = None;
+            }
+            Some(allocator::take_in(node, &Global).elem)
+        }
+    }
+
+    // Adds `elem` at the end of a lap: just before the current element,
+    // which stays current.
+    pub fn push_back(&mut self, elem: T) {
+        match self.cur {
+            None => self.insert_here(elem),
+            Some(cur) => unsafe { link_before(cur, self.new_node(elem)) },
+        }
+    }
+
+    fn new_node(&self, elem: T) -> NonNull<Node<T>> {
+        let node = allocator::alloc_in(
+            Node {
+                elem,
+                next: NonNull::dangling(),
+                prev: NonNull::dangling(),
+            },
+            &Global,
+        );
+        unsafe {
+            (*node.as_ptr()).next = node;
+            (*node.as_ptr()).prev = node;
+        }
+        node
+    }
+}
+
+// Splices the ring `node` belongs to in just before `at`, so that going
+// forward from `node` ends up back at `at`.
+unsafe fn link_before<T>(at: NonNull<Node<T>>, node: NonNull<Node<T>>) {
+    let before = (*at.as_ptr()).prev;
+    let last = (*node.as_ptr()).prev;
+    (*before.as_ptr()).next = node;
+    (*node.as_ptr()).prev = before;
+    (*last.as_ptr()).next = at;
+    (*at.as_ptr()).prev = last;
+}
+
+/********** Split and join **********/
+
+impl<T> Ring<T> {
+    // Marks the current element as the start of the span for split().
+    pub fn set_mark(&mut self) {
+        self.mark = self.cur;
+    }
+
+    // Cuts the elements from the mark up to, but not including, the current
+    // element out into a new ring whose current element is the marked one.
+    // O(1). Clears the mark; without one, or with the mark on the current
+    // element, the new ring is empty.
+    pub fn split(&mut self) -> Ring<T> {
+        let mut rest = Ring::new();
+        let (first, cur) = match (self.mark.take(), self.cur) {
+            (Some(first), Some(cur)) if first != cur => (first, cur),
+            _ => return rest,
+        };
+        unsafe {
+            let before = (*first.as_ptr()).prev;
+            let last = (*cur.as_ptr()).prev;
+            (*before.as_ptr()).next = cur;
+            (*cur.as_ptr()).prev = before;
+            (*last.as_ptr()).next = first;
+            (*first.as_ptr()).prev = last;
+        }
+        rest.cur = Some(first);
+        rest
+    }
+
+    // Moves every element of `other` to the end of this ring's lap, just
+    // before the current element, in the order a lap of `other` would visit
+    // them. O(1). Leaves `other` empty.
+    pub fn join(&mut self, other: &mut Ring<T>) {
+        other.mark = None;
+        let theirs = match other.cur.take() {
+            Some(theirs) => theirs,
+            None => return,
+        };
+        match self.cur {
+            Some(cur) => unsafe { link_before(cur, theirs) },
+            None => self.cur = Some(theirs),
+        }
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        self.drop_nodes();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T> Drop for Ring<T> {
+    fn drop(&mut self) {
+        self.drop_nodes();
+    }
+}
+
+impl<T> Ring<T> {
+    fn drop_nodes(&mut self) {
+        // Keeps removing during the unwind if an element's destructor
+        // panics, so the rest of the nodes are still freed.
+        struct DropGuard<'a, T>(&'a mut Ring<T>);
+
+        impl<T> Drop for DropGuard<'_, T> {
+            fn drop(&mut self) {
+                while self.0.remove_here().is_some() {}
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.remove_here().is_some() {}
+    }
+}
+
+impl<T> Default for Ring<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Extend<T> for Ring<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+// The first element ends up current.
+impl<T> FromIterator<T> for Ring<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut ring = Ring::new();
+        ring.extend(iter);
+        ring
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Ring<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/********** Iterators **********/
+
+// One lap, starting at the current element.
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    start: Link<T>,
+    _marker: PhantomData<&'a T>,
+}
+
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+
+impl<T> Ring<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.cur,
+            start: self.cur,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = unsafe { &*self.next?.as_ptr() };
+        self.next = Some(node.next).filter(|&next| Some(next) != self.start);
+        Some(&node.elem)
+    }
+}
+
+// Removes elements from the current one onwards, so one lap's worth.
+pub struct IntoIter<T>(Ring<T>);
+
+impl<T> IntoIterator for Ring<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.remove_here()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Ring;
+    use crate::panicky::{panics, Bomb};
+    use core::cell::Cell;
+    use std::vec::Vec;
+
+    fn lap(ring: &Ring<i32>) -> Vec<i32> {
+        ring.iter().copied().collect()
+    }
+
+    #[test]
+    fn basics() {
+        let mut ring = Ring::new();
+        assert!(ring.is_empty());
+        assert_eq!(ring.current(), None);
+        ring.advance();
+        ring.retreat();
+        assert_eq!(ring.remove_here(), None);
+        assert!(ring.iter().next().is_none());
+
+        ring.insert_here(1);
+        ring.advance();
+        assert_eq!(ring.current(), Some(&1));
+        ring.insert_here(2);
+        ring.insert_here(3);
+        assert_eq!(lap(&ring), [3, 2, 1]);
+
+        // No ends in either direction.
+        ring.advance_by(7);
+        assert_eq!(ring.current(), Some(&2));
+        ring.retreat();
+        ring.retreat();
+        assert_eq!(ring.current(), Some(&1));
+        *ring.current_mut().unwrap() = 10;
+        assert_eq!(lap(&ring), [10, 3, 2]);
+
+        assert_eq!(ring.remove_here(), Some(10));
+        assert_eq!(ring.current(), Some(&3));
+        ring.insert_here(4);
+        assert_eq!(ring.remove_here(), Some(4));
+        assert_eq!(lap(&ring), [3, 2]);
+        assert_eq!(ring.into_iter().collect::<Vec<_>>(), [3, 2]);
+    }
+
+    #[test]
+    fn collect_and_extend_keep_current() {
+        let mut ring: Ring<i32> = (1..=3).collect();
+        ring.advance();
+        ring.extend(4..=5);
+        assert_eq!(lap(&ring), [2, 3, 1, 4, 5]);
+        assert_eq!(format!("{:?}", ring), "[2, 3, 1, 4, 5]");
+    }
+
+    #[test]
+    fn josephus() {
+        // 7 people, every third one leaves.
+        let mut ring: Ring<i32> = (1..=7).collect();
+        let mut out = Vec::new();
+        while !ring.is_empty() {
+            ring.advance_by(2);
+            out.push(ring.remove_here().unwrap());
+        }
+        assert_eq!(out, [3, 6, 2, 7, 5, 1, 4]);
+    }
+
+    #[test]
+    fn split_and_join() {
+        let mut ring: Ring<i32> = (1..=6).collect();
+        ring.advance_by(2);
+        ring.set_mark();
+        ring.advance_by(2);
+        let mut cut = ring.split();
+        assert_eq!(lap(&cut), [3, 4]);
+        assert_eq!(lap(&ring), [5, 6, 1, 2]);
+
+        // The mark is used up, and a mark on current cuts nothing.
+        assert!(ring.split().is_empty());
+        ring.set_mark();
+        assert!(ring.split().is_empty());
+
+        // Everything but the current element.
+        ring.set_mark();
+        ring.advance();
+        ring.retreat();
+        ring.retreat();
+        let mut rest = ring.split();
+        ring.advance();
+        assert_eq!(lap(&ring), [2]);
+        assert_eq!(lap(&rest), [5, 6, 1]);
+
+        cut.advance();
+        ring.join(&mut cut);
+        assert!(cut.is_empty());
+        assert_eq!(lap(&ring), [2, 4, 3]);
+        ring.join(&mut Ring::new());
+        cut.join(&mut ring);
+        assert_eq!(lap(&cut), [2, 4, 3]);
+        rest.join(&mut cut);
+        assert_eq!(lap(&rest), [5, 6, 1, 2, 4, 3]);
+    }
+
+    #[test]
+    fn removing_the_mark_clears_it() {
+        let mut ring: Ring<i32> = (1..=4).collect();
+        ring.set_mark();
+        ring.remove_here();
+        ring.advance();
+        assert!(ring.split().is_empty());
+        assert_eq!(lap(&ring), [3, 4, 2]);
+    }
+
+    #[test]
+    fn drop_survives_panicking_destructor() {
+        let drops = Cell::new(0);
+        let mut ring = Ring::new();
+        for i in 0..5 {
+            ring.push_back(if i == 2 { Bomb::armed(&drops) } else { Bomb::new(&drops) });
+        }
+        assert!(panics(move || drop(ring)));
+        assert_eq!(drops.get(), 5);
+    }
+}
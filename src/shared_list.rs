@@ -1,9 +1,9 @@
 #![allow(warnings)]
 
-use std::rc::Rc;
+use alloc::rc::Rc;
 
 #[derive(Debug)]
-struct List<T> {
+pub struct List<T> {
     head: NodePtr<T>,
 }
 
@@ -16,11 +16,11 @@ struct Node<T> {
 }
 
 impl<T> List<T> {
-    fn new() -> List<T> {
+    pub fn new() -> List<T> {
         List { head: None }
     }
 
-    fn append(&self, elem: T) -> List<T> {
+    pub fn append(&self, elem: T) -> List<T> {
         let new_node = Node {
             elem,
             //next: self.head.as_ref().map(|node| Rc::clone(&node)), <- My code
@@ -31,7 +31,7 @@ impl<T> List<T> {
         }
     }
 
-    fn head(&self) -> Option<&T> {
+    pub fn head(&self) -> Option<&T> {
         self.head.as_ref().map(|node| &node.elem)
     }
 
@@ -53,7 +53,7 @@ impl<T> List<T> {
         }
     }
 
-    fn tail(&self) -> List<T> {
+    pub fn tail(&self) -> List<T> {
         if let Some(cur_head) = self.head.as_ref() {
             if let Some(new_head) = cur_head.next.as_ref() {
                 return List {
@@ -65,12 +65,31 @@ impl<T> List<T> {
         return List { head: None };
     }
 
+    // Whether both lists start at the same node, and so are the same list.
+    // For lists from the same Interner that's the same as being equal.
+    pub fn ptr_eq(&self, other: &List<T>) -> bool {
+        match (&self.head, &other.head) {
+            (Some(ours), Some(theirs)) => Rc::ptr_eq(ours, theirs),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
     pub fn tail_from_book(&self) {
         let head = self.head.as_ref().and_then(|node| node.next.clone());
     }
     
 }
 
+// Cloning a persistent list only bumps the head's refcount.
+impl<T> Clone for List<T> {
+    fn clone(&self) -> Self {
+        List {
+            head: self.head.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 struct Point(i32, i32);
 
@@ -94,12 +113,12 @@ mod tests {
     }
 }
 
-struct ListIter<'a, T> {
+pub struct ListIter<'a, T> {
     next: Option<&'a Node<T>>,
 }
 
 impl<T> List<T> {
-    fn iter(&self) -> ListIter<T> {
+    pub fn iter(&self) -> ListIter<T> {
         ListIter {
             next: self.head.as_ref().map(|node| &**node),
         }
@@ -116,6 +135,298 @@ impl<'a, T> Iterator for ListIter<'a, T> {
     }
 }
 
+/********** Parallel Iterator **********/
+
+#[cfg(feature = "rayon")]
+pub use self::parallel::ParIter;
+
+#[cfg(feature = "rayon")]
+mod parallel {
+    use rayon::iter::plumbing::{bridge, Consumer, ProducerCallback, UnindexedConsumer};
+    use rayon::prelude::*;
+
+    use super::{List, Node};
+    use crate::par::{Cursor, Nodes};
+
+    struct At<'a, T>(&'a Node<T>);
+
+    impl<T> Clone for At<'_, T> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+
+    impl<T> Copy for At<'_, T> {}
+
+    // The nodes are behind Rcs, but this only reads the element and the next
+    // pointer and never touches a reference count. Nothing can drop or
+    // change the nodes while the &List it came from is borrowed.
+    unsafe impl<T: Sync> Send for At<'_, T> {}
+
+    impl<'a, T: Sync> Cursor for At<'a, T> {
+        type Item = &'a T;
+        fn step(self) -> (&'a T, Option<Self>) {
+            (&self.0.elem, self.0.next.as_deref().map(At))
+        }
+    }
+
+    // Splits by node count; the nodes are counted once when it is created.
+    pub struct ParIter<'a, T>(Nodes<At<'a, T>>);
+
+    impl<'a, T: Sync> IntoParallelIterator for &'a List<T> {
+        type Item = &'a T;
+        type Iter = ParIter<'a, T>;
+
+        fn into_par_iter(self) -> ParIter<'a, T> {
+            ParIter(Nodes::new(self.head.as_deref().map(At), self.iter().count()))
+        }
+    }
+
+    impl<'a, T: Sync> ParallelIterator for ParIter<'a, T> {
+        type Item = &'a T;
+
+        fn drive_unindexed<C: UnindexedConsumer<&'a T>>(self, consumer: C) -> C::Result {
+            bridge(self, consumer)
+        }
+
+        fn opt_len(&self) -> Option<usize> {
+            Some(self.0.len())
+        }
+    }
+
+    impl<'a, T: Sync> IndexedParallelIterator for ParIter<'a, T> {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn drive<C: Consumer<&'a T>>(self, consumer: C) -> C::Result {
+            bridge(self, consumer)
+        }
+
+        fn with_producer<CB: ProducerCallback<&'a T>>(self, callback: CB) -> CB::Output {
+            callback.callback(self.0)
+        }
+    }
+}
+
+/********** Hash-consing **********/
+
+#[cfg(feature = "std")]
+pub use self::interner::Interner;
+
+#[cfg(feature = "std")]
+mod interner {
+    use alloc::rc::{Rc, Weak};
+    use core::hash::{BuildHasher, Hash};
+    use std::collections::hash_map::RandomState;
+    use std::collections::HashMap;
+    use std::vec::Vec;
+
+    use super::{List, Node, NodePtr};
+
+    // Builds lists out of one shared pool of nodes, never making two nodes
+    // with the same element and the same next node. Equal lists from the
+    // same interner are then the very same nodes: comparing them is
+    // List::ptr_eq, and each distinct suffix is stored once.
+    //
+    // The table only holds Weak pointers, so a node goes once the last list
+    // using it does. Its entry stays behind until swept, along with the
+    // Rc's allocation; sweeps happen as the table grows, so they stay
+    // amortized O(1) per node.
+    pub struct Interner<T, S = RandomState> {
+        // Nodes by the hash of their element and next pointer.
+        table: HashMap<u64, Vec<Weak<Node<T>>>>,
+        hasher: S,
+        entries: usize,
+        // Sweep out dead entries once there are this many.
+        sweep_at: usize,
+    }
+
+    const MIN_SWEEP: usize = 32;
+
+    impl<T: Hash + Eq> Interner<T> {
+        pub fn new() -> Self {
+            Interner::with_hasher(RandomState::new())
+        }
+    }
+
+    impl<T: Hash + Eq> Default for Interner<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    fn as_ptr<T>(link: &NodePtr<T>) -> *const Node<T> {
+        link.as_ref().map_or(core::ptr::null(), Rc::as_ptr)
+    }
+
+    impl<T: Hash + Eq, S: BuildHasher> Interner<T, S> {
+        pub fn with_hasher(hasher: S) -> Self {
+            Interner {
+                table: HashMap::new(),
+                hasher,
+                entries: 0,
+                sweep_at: MIN_SWEEP,
+            }
+        }
+
+        // `list` with `elem` in front, like List::append, but reusing the
+        // node if there is one already. `list` should come from this
+        // interner, or only its head is shared; see intern.
+        pub fn append(&mut self, list: &List<T>, elem: T) -> List<T> {
+            let next = list.head.clone();
+            let node = match self.find(&elem, &next) {
+                Some(node) => node,
+                None => self.insert(Rc::new(Node { elem, next })),
+            };
+            List { head: Some(node) }
+        }
+
+        // The interned copy of `list`, equal to it. Nodes of `list` are
+        // reused where they can be, so interning a list from this interner
+        // just returns it.
+        pub fn intern(&mut self, list: &List<T>) -> List<T>
+        where
+            T: Clone,
+        {
+            let mut nodes = Vec::new();
+            let mut link = &list.head;
+            while let Some(node) = link {
+                nodes.push(node);
+                link = &node.next;
+            }
+            let mut interned = List::new();
+            for node in nodes.into_iter().rev() {
+                // A node already followed by the interned rest of the list
+                // can be interned as it is.
+                interned = if as_ptr(&node.next) == as_ptr(&interned.head) {
+                    let node = match self.find(&node.elem, &node.next) {
+                        Some(other) => other,
+                        None => self.insert(node.clone()),
+                    };
+                    List { head: Some(node) }
+                } else {
+                    self.append(&interned, node.elem.clone())
+                };
+            }
+            interned
+        }
+
+        fn hash(&self, elem: &T, next: &NodePtr<T>) -> u64 {
+            self.hasher.hash_one((elem, as_ptr(next)))
+        }
+
+        // Also drops the dead entries it comes across.
+        fn find(&mut self, elem: &T, next: &NodePtr<T>) -> Option<Rc<Node<T>>> {
+            let hash = self.hash(elem, next);
+            let bucket = self.table.get_mut(&hash)?;
+            let before = bucket.len();
+            bucket.retain(|node| node.strong_count() > 0);
+            self.entries -= before - bucket.len();
+            bucket
+                .iter()
+                .filter_map(Weak::upgrade)
+                .find(|node| node.elem == *elem && as_ptr(&node.next) == as_ptr(next))
+        }
+
+        fn insert(&mut self, node: Rc<Node<T>>) -> Rc<Node<T>> {
+            let hash = self.hash(&node.elem, &node.next);
+            self.table.entry(hash).or_default().push(Rc::downgrade(&node));
+            self.entries += 1;
+            if self.entries >= self.sweep_at {
+                self.sweep();
+            }
+            node
+        }
+
+        // How many nodes are in use.
+        pub fn len(&self) -> usize {
+            let live = |bucket: &Vec<Weak<Node<T>>>| {
+                bucket.iter().filter(|node| node.strong_count() > 0).count()
+            };
+            self.table.values().map(live).sum()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        fn sweep(&mut self) {
+            self.table.retain(|_, bucket| {
+                bucket.retain(|node| node.strong_count() > 0);
+                !bucket.is_empty()
+            });
+            self.entries = self.table.values().map(Vec::len).sum();
+            self.sweep_at = (2 * self.entries).max(MIN_SWEEP);
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::super::List;
+        use super::Interner;
+        use std::vec::Vec;
+
+        fn build(interner: &mut Interner<i32>, elems: &[i32]) -> List<i32> {
+            elems
+                .iter()
+                .rev()
+                .fold(List::new(), |list, &elem| interner.append(&list, elem))
+        }
+
+        #[test]
+        fn equal_lists_are_the_same_nodes() {
+            let mut interner = Interner::new();
+            let a = build(&mut interner, &[1, 2, 3]);
+            let b = build(&mut interner, &[1, 2, 3]);
+            assert!(a.ptr_eq(&b));
+            assert_eq!(interner.len(), 3);
+
+            // Only the shared suffix is shared.
+            let c = build(&mut interner, &[0, 2, 3]);
+            assert!(!a.ptr_eq(&c));
+            assert!(a.tail().ptr_eq(&c.tail()));
+            assert_eq!(interner.len(), 4);
+            let d = build(&mut interner, &[1, 2]);
+            assert!(!a.ptr_eq(&d));
+            assert!(!a.tail().tail().ptr_eq(&d.tail()));
+            assert!(List::<i32>::new().ptr_eq(&List::new()));
+            assert!(!a.ptr_eq(&List::new()));
+        }
+
+        #[test]
+        fn intern_existing_lists() {
+            let mut interner = Interner::new();
+            let plain = List::new().append(3).append(2).append(1);
+            let interned = interner.intern(&plain);
+            // Nothing to share with yet, so it keeps plain's nodes.
+            assert!(interned.ptr_eq(&plain));
+            assert!(interner.intern(&interned).ptr_eq(&interned));
+            assert!(build(&mut interner, &[1, 2, 3]).ptr_eq(&plain));
+
+            let other = List::new().append(3).append(2).append(0);
+            let interned = interner.intern(&other);
+            assert!(!interned.ptr_eq(&other));
+            assert!(interned.tail().ptr_eq(&plain.tail()));
+            assert_eq!(interned.iter().copied().collect::<Vec<_>>(), [0, 2, 3]);
+            assert_eq!(interner.len(), 4);
+        }
+
+        #[test]
+        fn unused_nodes_are_reclaimed() {
+            let mut interner = Interner::new();
+            let keep = build(&mut interner, &[7, 8]);
+            for i in 0..1000 {
+                build(&mut interner, &[i, i + 1, i + 2]);
+                assert!(interner.entries <= 64);
+            }
+            assert_eq!(interner.len(), 2);
+            drop(keep);
+            assert!(interner.is_empty());
+        }
+    }
+}
+
 #[cfg(test)]
 mod test_from_book {
     use super::List;
@@ -1,67 +1,368 @@
-#![allow(warnings)]
-
-#[derive(Debug)]
-struct List<T> {
+use alloc::boxed::Box;
+use core::cmp::Ordering;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr::NonNull;
+
+use crate::allocator::{self, AllocError, Allocator, Global};
+use crate::merge_sort::{self, Run, Sortable};
+
+// Nodes live in memory handed out by `A`; the list owns them through raw
+// pointers, so the PhantomData tells dropck that we own boxed nodes.
+pub struct List<T, A: Allocator = Global> {
     head: NodePtr<T>,
+    alloc: A,
+    _owns: PhantomData<Box<Node<T>>>,
 }
 
-type NodePtr<T> = Option<Box<Node<T>>>;
+type NodePtr<T> = Option<NonNull<Node<T>>>;
+
+unsafe impl<T: Send, A: Allocator + Send> Send for List<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for List<T, A> {}
 
-#[derive(Debug)]
 struct Node<T> {
     elem: T,
     next: NodePtr<T>,
 }
 
 impl<T> List<T> {
-    fn new() -> Self {
-        List { head: None }
+    pub fn new() -> Self {
+        List::new_in(Global)
+    }
+}
+
+impl<T> Default for List<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, A: Allocator> List<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        List {
+            head: None,
+            alloc,
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn push(&mut self, elem: T) {
+        let new_node = Node {
+            elem,
+            next: self.head.take(),
+        };
+        self.head = Some(allocator::alloc_in(new_node, &self.alloc));
     }
 
-    fn push(&mut self, elem: T) {
-        let mut new_node = Node {
+    // Like push, but hands the element back if the allocator can't provide
+    // a node.
+    pub fn try_push(&mut self, elem: T) -> Result<(), (T, AllocError)> {
+        let new_node = Node {
             elem,
             next: self.head.take(),
         };
-        self.head = Some(Box::new(new_node));
+        match allocator::try_alloc_in(new_node, &self.alloc) {
+            Ok(node) => {
+                self.head = Some(node);
+                Ok(())
+            }
+            Err((node, err)) => {
+                self.head = node.next;
+                Err((node.elem, err))
+            }
+        }
     }
 
-    fn pop(&mut self) -> Option<T> {
+    pub fn pop(&mut self) -> Option<T> {
         self.head.take().map(|prev_node| {
+            let prev_node = unsafe { allocator::take_in(prev_node, &self.alloc) };
             self.head = prev_node.next;
             prev_node.elem
         })
     }
 
-    fn peek(&self) -> Option<&T> {
-        self.head.as_ref().map(|noderef| &noderef.elem)
+    pub fn peek(&self) -> Option<&T> {
+        self.head.as_ref().map(|noderef| unsafe { &noderef.as_ref().elem })
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.head.as_mut().map(|noderef| unsafe { &mut noderef.as_mut().elem })
+    }
+}
+
+/********** Splicing **********/
+impl<T> List<T> {
+    // Moves every element of `other` onto the end of this list, leaving
+    // `other` empty. The nodes are relinked rather than copied, so both lists
+    // have to free through the same allocator; hence Global only.
+    pub fn append(&mut self, other: &mut Self) {
+        let mut link = &mut self.head;
+        while let Some(node) = *link {
+            link = unsafe { &mut (*node.as_ptr()).next };
+        }
+        *link = other.head.take();
+    }
+}
+
+impl<T, A: Allocator + Clone> List<T, A> {
+    // Keeps the first `at` elements and returns the rest as a new list.
+    // Panics if `at` is past the end of the list.
+    pub fn split_off(&mut self, at: usize) -> Self {
+        let mut link = &mut self.head;
+        for _ in 0..at {
+            match *link {
+                Some(node) => link = unsafe { &mut (*node.as_ptr()).next },
+                None => panic!("split_off index out of bounds"),
+            }
+        }
+        let mut rest = List::new_in(self.alloc.clone());
+        rest.head = link.take();
+        rest
+    }
+}
+
+impl<T, A: Allocator> List<T, A> {
+    // Keeps only the elements for which `f` returns true. Each node is
+    // unlinked and freed before its element is dropped, so the list stays
+    // valid if `f` or a destructor panics.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, mut f: F) {
+        let mut link = &mut self.head;
+        while let Some(node) = *link {
+            if f(unsafe { &node.as_ref().elem }) {
+                link = unsafe { &mut (*node.as_ptr()).next };
+            } else {
+                let node = unsafe { allocator::take_in(node, &self.alloc) };
+                *link = node.next;
+                drop(node.elem);
+            }
+        }
+    }
+}
+
+/********** Sorting **********/
+
+impl<T, A: Allocator> List<T, A> {
+    // See merge_sort::sort_by.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F) {
+        merge_sort::sort_by(self, compare);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Send, A: Allocator> List<T, A> {
+    // merge_sort::par_sort_by, with a run per core. Counts the list first.
+    pub fn par_sort_by<F>(&mut self, compare: F)
+    where
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        let len = self.iter().count();
+        merge_sort::par_sort_by(self, len, merge_sort::par_runs(len), &compare);
+    }
+}
+
+// Nodes lent out of a list for sorting, with the last one kept so appending
+// is O(1). The list frees them once they're back.
+pub(crate) struct Chain<T> {
+    head: NodePtr<T>,
+    tail: NodePtr<T>,
+}
+
+// Nothing outside the chain links to its nodes.
+unsafe impl<T: Send> Send for Chain<T> {}
+
+impl<T> Chain<T> {
+    fn new() -> Self {
+        Chain {
+            head: None,
+            tail: None,
+        }
+    }
+
+    // Moves the first node onto the back of `to`. There must be one.
+    fn move_front(&mut self, to: &mut Chain<T>) {
+        let node = self.head.unwrap();
+        self.head = unsafe { (*node.as_ptr()).next.take() };
+        if self.head.is_none() {
+            self.tail = None;
+        }
+        to.append(&mut Chain {
+            head: Some(node),
+            tail: Some(node),
+        });
+    }
+}
+
+impl<T> Run for Chain<T> {
+    type Elem = T;
+
+    fn empty(&self) -> Self {
+        Chain::new()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        if at == 0 {
+            return mem::replace(self, Chain::new());
+        }
+        let mut last = match self.head {
+            Some(head) => head,
+            None => return Chain::new(),
+        };
+        for _ in 1..at {
+            match unsafe { (*last.as_ptr()).next } {
+                Some(next) => last = next,
+                None => return Chain::new(),
+            }
+        }
+        let head = unsafe { (*last.as_ptr()).next.take() };
+        let rest = Chain {
+            tail: head.and(self.tail),
+            head,
+        };
+        self.tail = Some(last);
+        rest
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        let other = mem::replace(other, Chain::new());
+        let other_head = match other.head {
+            Some(head) => head,
+            None => return,
+        };
+        match self.tail {
+            Some(tail) => unsafe { (*tail.as_ptr()).next = Some(other_head) },
+            None => self.head = Some(other_head),
+        }
+        self.tail = other.tail;
     }
 
-    fn peek_mut(&mut self) -> Option<&mut T> {
-        self.head.as_mut().map(|noderef| &mut noderef.elem)
+    fn merge<F: FnMut(&T, &T) -> Ordering>(&mut self, other: &mut Self, compare: &mut F) {
+        // Takes the smaller front node onto `done` until one side runs out.
+        // Dropping the guard, on return or unwind, leaves `done` and then
+        // this chain's leftovers here; `other` holds on to its own.
+        struct Merging<'a, T> {
+            chain: &'a mut Chain<T>,
+            done: Chain<T>,
+        }
+
+        impl<T> Drop for Merging<'_, T> {
+            fn drop(&mut self) {
+                self.done.append(self.chain);
+                mem::swap(self.chain, &mut self.done);
+            }
+        }
+
+        let mut m = Merging {
+            chain: self,
+            done: Chain::new(),
+        };
+        while let (Some(ours), Some(theirs)) = (m.chain.head, other.head) {
+            let theirs_first =
+                unsafe { compare(&(*theirs.as_ptr()).elem, &(*ours.as_ptr()).elem) } == Ordering::Less;
+            if theirs_first {
+                other.move_front(&mut m.done);
+            } else {
+                m.chain.move_front(&mut m.done);
+            }
+        }
+        m.done.append(other);
+    }
+}
+
+impl<T, A: Allocator> Sortable for List<T, A> {
+    type Run = Chain<T>;
+
+    fn take_run(&mut self) -> Chain<T> {
+        let mut tail = self.head;
+        while let Some(next) = tail.and_then(|node| unsafe { (*node.as_ptr()).next }) {
+            tail = Some(next);
+        }
+        Chain {
+            head: self.head.take(),
+            tail,
+        }
+    }
+
+    fn put_run(&mut self, run: Chain<T>) {
+        self.head = run.head;
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for List<T, A> {
+    fn clone(&self) -> Self {
+        let mut new_list = List::new_in(self.alloc.clone());
+        let mut tail = &mut new_list.head;
+        for elem in self.iter() {
+            let node = Node {
+                elem: elem.clone(),
+                next: None,
+            };
+            let node = allocator::alloc_in(node, &new_list.alloc);
+            *tail = Some(node);
+            tail = unsafe { &mut (*node.as_ptr()).next };
+        }
+        new_list
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for List<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T, A: Allocator> Drop for List<T, A> {
+    fn drop(&mut self) {
+        self.drop_nodes();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T, A: Allocator> Drop for List<T, A> {
+    fn drop(&mut self) {
+        self.drop_nodes();
+    }
+}
+
+impl<T, A: Allocator> List<T, A> {
+    fn drop_nodes(&mut self) {
+        // Keeps popping during the unwind if an element's destructor panics,
+        // so the rest of the nodes are still freed.
+        struct DropGuard<'a, T, A: Allocator>(&'a mut List<T, A>);
+
+        impl<T, A: Allocator> Drop for DropGuard<'_, T, A> {
+            fn drop(&mut self) {
+                while self.0.pop().is_some() {}
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.pop().is_some() {}
     }
 }
 
 /********** IntoIterator **********/
-struct ListIntoIter<T>(List<T>);
+pub struct ListIntoIter<T, A: Allocator = Global>(List<T, A>);
 
-impl<T> Iterator for ListIntoIter<T> {
+impl<T, A: Allocator> Iterator for ListIntoIter<T, A> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         self.0.pop()
     }
 }
 
-// Iterators
-impl<T> List<T> {
-    fn into_iter(self) -> ListIntoIter<T> {
-        ListIntoIter(self)
-    }
-}
-
-impl<T> IntoIterator for List<T> {
+impl<T, A: Allocator> IntoIterator for List<T, A> {
     type Item = T;
-    type IntoIter = ListIntoIter<T>;
+    type IntoIter = ListIntoIter<T, A>;
 
     fn into_iter(self) -> Self::IntoIter {
         ListIntoIter(self)
@@ -69,82 +370,224 @@ impl<T> IntoIterator for List<T> {
 }
 
 /********** Iterator **********/
-struct ListIter<'a, T> {
+pub struct ListIter<'a, T> {
     cur: &'a NodePtr<T>,
 }
 
+// The iterators hold node pointers, which opt them out of Send and Sync;
+// they are as thread-safe as the &T or &mut T they hand out.
+unsafe impl<T: Sync> Send for ListIter<'_, T> {}
+unsafe impl<T: Sync> Sync for ListIter<'_, T> {}
+
 impl<'a, T> Iterator for ListIter<'a, T> {
     type Item = &'a T;
     fn next(&mut self) -> Option<Self::Item> {
         match self.cur {
             None => None,
             Some(node) => {
-                let retval = &(**node).elem;
-                self.cur = &(**node).next;
-                Some(retval)
+                let node = unsafe { node.as_ref() };
+                self.cur = &node.next;
+                Some(&node.elem)
             }
         }
     }
 }
 
-impl<T> List<T> {
-    fn iter(&self) -> ListIter<T> {
+impl<T, A: Allocator> List<T, A> {
+    pub fn iter(&self) -> ListIter<'_, T> {
         ListIter { cur: &self.head }
     }
 }
 
 /********** Iterator Alternate **********/
-struct ListIterAlt<'a, T> {
+pub struct ListIterAlt<'a, T> {
     curnode: Option<&'a Node<T>>,
 }
 
+unsafe impl<T: Sync> Send for ListIterAlt<'_, T> {}
+unsafe impl<T: Sync> Sync for ListIterAlt<'_, T> {}
+
 impl<'a, T> Iterator for ListIterAlt<'a, T> {
     type Item = &'a T;
     fn next(&mut self) -> Option<Self::Item> {
         self.curnode.map(|node| {
-            self.curnode = node.next.as_ref().map(|node| &**node);
+            self.curnode = node.next.map(|node| unsafe { &*node.as_ptr() });
             &node.elem
         })
     }
 }
 
-impl<T> List<T> {
-    fn iter_alt(&self) -> ListIterAlt<T> {
-        let curnode = self.head.as_ref().map(|head| &**head);
+impl<T, A: Allocator> List<T, A> {
+    pub fn iter_alt(&self) -> ListIterAlt<'_, T> {
+        let curnode = self.head.map(|head| unsafe { &*head.as_ptr() });
         ListIterAlt { curnode }
     }
 }
 
 /********** Iterator Mut **********/
 
-struct ListIterMut<'a, T> {
+pub struct ListIterMut<'a, T> {
     next: Option<&'a mut Node<T>>,
 }
 
+unsafe impl<T: Send> Send for ListIterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for ListIterMut<'_, T> {}
+
 impl<'a, T> Iterator for ListIterMut<'a, T> {
     type Item = &'a mut T;
     fn next(&mut self) -> Option<Self::Item> {
-        let nodeptr = self.next.take().map(|node| {
-            self.next = node.next.as_mut().map(|node| &mut **node);
+        self.next.take().map(|node| {
+            self.next = node.next.map(|node| unsafe { &mut *node.as_ptr() });
             &mut node.elem
-        });
-        nodeptr
+        })
     }
 }
 
-impl<T> List<T> {
-    fn iter_mut(&mut self) -> ListIterMut<T> {
-        let nodeptr = self.head.as_mut().map(|node| &mut **node);
+impl<T, A: Allocator> List<T, A> {
+    pub fn iter_mut(&mut self) -> ListIterMut<'_, T> {
+        let nodeptr = self.head.map(|node| unsafe { &mut *node.as_ptr() });
         ListIterMut { next: nodeptr }
     }
 }
 
-#[derive(Debug)]
-struct Point(i32, i32);
+/********** Parallel Iterator **********/
+
+#[cfg(feature = "rayon")]
+pub use self::parallel::{IntoParIter, ParIter};
+
+#[cfg(feature = "rayon")]
+mod parallel {
+    use rayon::iter::plumbing::{bridge, Consumer, ProducerCallback, UnindexedConsumer};
+    use rayon::prelude::*;
+
+    use super::{List, Node};
+    use crate::allocator::{Allocator, Global};
+    use crate::par::{Cursor, Detach, Nodes, Owned};
+
+    struct At<'a, T>(&'a Node<T>);
+
+    impl<T> Clone for At<'_, T> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
+
+    impl<T> Copy for At<'_, T> {}
+
+    // Only ever reads the node, like a &T.
+    unsafe impl<T: Sync> Send for At<'_, T> {}
+
+    impl<'a, T: Sync> Cursor for At<'a, T> {
+        type Item = &'a T;
+        fn step(self) -> (&'a T, Option<Self>) {
+            let next = self.0.next.map(|node| At(unsafe { &*node.as_ptr() }));
+            (&self.0.elem, next)
+        }
+    }
+
+    // Splits by node count; the nodes are counted once when it is created.
+    pub struct ParIter<'a, T>(Nodes<At<'a, T>>);
+
+    impl<'a, T: Sync, A: Allocator> IntoParallelIterator for &'a List<T, A> {
+        type Item = &'a T;
+        type Iter = ParIter<'a, T>;
+
+        fn into_par_iter(self) -> ParIter<'a, T> {
+            let head = self.head.map(|node| At(unsafe { &*node.as_ptr() }));
+            ParIter(Nodes::new(head, self.iter().count()))
+        }
+    }
+
+    impl<'a, T: Sync> ParallelIterator for ParIter<'a, T> {
+        type Item = &'a T;
+
+        fn drive_unindexed<C: UnindexedConsumer<&'a T>>(self, consumer: C) -> C::Result {
+            bridge(self, consumer)
+        }
+
+        fn opt_len(&self) -> Option<usize> {
+            Some(self.0.len())
+        }
+    }
+
+    impl<'a, T: Sync> IndexedParallelIterator for ParIter<'a, T> {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn drive<C: Consumer<&'a T>>(self, consumer: C) -> C::Result {
+            bridge(self, consumer)
+        }
+
+        fn with_producer<CB: ProducerCallback<&'a T>>(self, callback: CB) -> CB::Output {
+            callback.callback(self.0)
+        }
+    }
+
+    impl<T: Send, A: Allocator + Clone + Send> Detach for List<T, A> {
+        type Item = T;
+
+        fn detach(&mut self, at: usize) -> Self {
+            self.split_off(at)
+        }
+
+        fn pop_front(&mut self) -> Option<T> {
+            self.pop()
+        }
+    }
+
+    // Splits by node count like ParIter, cutting the list at each split.
+    pub struct IntoParIter<T, A: Allocator = Global>(Owned<List<T, A>>);
+
+    impl<T: Send, A: Allocator + Clone + Send> IntoParallelIterator for List<T, A> {
+        type Item = T;
+        type Iter = IntoParIter<T, A>;
+
+        fn into_par_iter(self) -> IntoParIter<T, A> {
+            let len = self.iter().count();
+            IntoParIter(Owned::new(self, len))
+        }
+    }
+
+    impl<T: Send, A: Allocator + Clone + Send> ParallelIterator for IntoParIter<T, A> {
+        type Item = T;
+
+        fn drive_unindexed<C: UnindexedConsumer<T>>(self, consumer: C) -> C::Result {
+            bridge(self, consumer)
+        }
+
+        fn opt_len(&self) -> Option<usize> {
+            Some(self.0.len())
+        }
+    }
+
+    impl<T: Send, A: Allocator + Clone + Send> IndexedParallelIterator for IntoParIter<T, A> {
+        fn len(&self) -> usize {
+            self.0.len()
+        }
+
+        fn drive<C: Consumer<T>>(self, consumer: C) -> C::Result {
+            bridge(self, consumer)
+        }
+
+        fn with_producer<CB: ProducerCallback<T>>(self, callback: CB) -> CB::Output {
+            callback.callback(self.0)
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::allocator::test::{Counting, Failing};
+    use crate::allocator::AllocError;
+    use crate::panicky::{panics, Bomb, Touchy};
+    use core::cell::Cell;
+    use std::string::ToString;
+    use std::vec::Vec;
+
+    #[derive(Debug)]
+    struct Point(i32, i32);
 
     #[test]
     fn basic_push_pop_peek() {
@@ -172,10 +615,19 @@ mod tests {
         assert_eq!(lst.peek(), None);
     }
 
+    #[test]
+    fn debug_shows_elements() {
+        let mut lst = List::new();
+        assert_eq!(format!("{:?}", lst), "[]");
+        lst.push(2);
+        lst.push(1);
+        assert_eq!(format!("{:?}", lst), "[1, 2]");
+    }
+
     #[test]
     fn into_iter_test() {
         let mut lst: List<i32> = List::new();
-        let elems = vec![-10, 27, 5, 2, 0, 11];
+        let elems = [-10, 27, 5, 2, 0, 11];
 
         for &elem in elems.iter() {
             lst.push(elem);
@@ -189,7 +641,7 @@ mod tests {
     #[test]
     fn iter_test() {
         let mut lst: List<Point> = List::new();
-        let elems = vec![-10, 27, 5, 2, 0, 11];
+        let elems = [-10, 27, 5, 2, 0, 11];
 
         for &elem in elems.iter() {
             lst.push(Point(elem, -elem));
@@ -202,7 +654,7 @@ mod tests {
     #[test]
     fn iter_mut_test() {
         let mut lst: List<Point> = List::new();
-        let elems = vec![-10, 27, 5, 2, 0, 11];
+        let elems = [-10, 27, 5, 2, 0, 11];
 
         for &elem in elems.iter() {
             lst.push(Point(elem, elem * 2));
@@ -230,8 +682,158 @@ mod tests {
         assert_eq!(iter.next(), Some(&mut 2));
         assert_eq!(iter.next(), Some(&mut 1));
     }
+
+    #[test]
+    fn frees_every_node() {
+        let counting = Counting::default();
+        {
+            let mut lst = List::new_in(&counting);
+            for i in 0..10 {
+                lst.push(i.to_string());
+            }
+            assert_eq!(counting.live(), 10);
+
+            assert_eq!(lst.pop(), Some("9".to_string()));
+            assert_eq!(counting.live(), 9);
+
+            let mut iter = lst.into_iter();
+            assert_eq!(iter.next(), Some("8".to_string()));
+            // Dropping the half-consumed iterator frees the rest.
+        }
+        assert_eq!(counting.allocs.get(), 10);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn clone_keeps_order() {
+        let mut lst = List::new();
+        lst.push(1);
+        lst.push(2);
+        lst.push(3);
+
+        let copy = lst.clone();
+        lst.pop();
+        assert_eq!(copy.iter().copied().collect::<std::vec::Vec<_>>(), [3, 2, 1]);
+        assert_eq!(List::<i32>::new().clone().pop(), None);
+    }
+
+    #[test]
+    fn try_push_sheds_on_alloc_failure() {
+        let failing = Failing::new(2);
+        let mut lst = List::new_in(&failing);
+
+        assert_eq!(lst.try_push(1), Ok(()));
+        assert_eq!(lst.try_push(2), Ok(()));
+        assert_eq!(lst.try_push(3), Err((3, AllocError)));
+        assert_eq!(lst.peek(), Some(&2));
+
+        assert_eq!(lst.pop(), Some(2));
+        assert_eq!(lst.try_push(3), Ok(()));
+        assert_eq!(lst.pop(), Some(3));
+        assert_eq!(lst.pop(), Some(1));
+        assert_eq!(lst.pop(), None);
+        assert_eq!(failing.live(), 0);
+    }
+
+    #[test]
+    fn drop_survives_panicking_destructor() {
+        let counting = Counting::default();
+        let drops = Cell::new(0);
+        let mut lst = List::new_in(&counting);
+        for i in 0..5 {
+            lst.push(if i == 3 { Bomb::armed(&drops) } else { Bomb::new(&drops) });
+        }
+        assert!(panics(move || drop(lst)));
+        assert_eq!(drops.get(), 5);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn retain() {
+        let counting = Counting::default();
+        let mut lst = List::new_in(&counting);
+        for i in 0..7 {
+            lst.push(i);
+        }
+        lst.retain(|&x| x % 3 != 0);
+        assert_eq!(lst.iter().copied().collect::<std::vec::Vec<_>>(), [5, 4, 2, 1]);
+        assert_eq!(counting.live(), 4);
+        lst.retain(|_| false);
+        assert_eq!(lst.pop(), None);
+        assert_eq!(counting.live(), 0);
+    }
+
+    #[test]
+    fn retain_survives_panics() {
+        let mut lst = List::new();
+        for i in [5, 4, -3, 2, 1, 0] {
+            lst.push(Touchy(i));
+        }
+        assert!(panics(|| lst.retain(|x| *x < Touchy(2))));
+        assert_eq!(lst.iter().map(|x| x.0).collect::<std::vec::Vec<_>>(), [0, 1, -3, 4, 5]);
+
+        let drops = Cell::new(0);
+        let mut lst = List::new();
+        for i in 0..5 {
+            lst.push((i, if i == 2 { Bomb::armed(&drops) } else { Bomb::new(&drops) }));
+        }
+        assert!(panics(|| lst.retain(|&(i, _)| i % 2 == 1)));
+        // 4 and 2 were unlinked before 2's destructor went off.
+        assert_eq!(drops.get(), 2);
+        assert_eq!(lst.iter().map(|x| x.0).collect::<std::vec::Vec<_>>(), [3, 1, 0]);
+    }
+
+    impl<E, A: Allocator> merge_sort::test::Subject<E, A> for List<E, A> {
+        fn make(elems: Vec<E>, alloc: A) -> Self {
+            let mut lst = List::new_in(alloc);
+            for elem in elems.into_iter().rev() {
+                lst.push(elem);
+            }
+            lst
+        }
+
+        fn read<R>(&self, f: impl FnMut(&E) -> R) -> Vec<R> {
+            self.iter().map(f).collect()
+        }
+
+        fn sort_by(&mut self, compare: impl FnMut(&E, &E) -> Ordering) {
+            List::sort_by(self, compare);
+        }
+
+        #[cfg(feature = "std")]
+        fn par_sort_by(&mut self, compare: impl Fn(&E, &E) -> Ordering + Sync)
+        where
+            E: Send,
+            A: Send,
+        {
+            List::par_sort_by(self, compare);
+        }
+    }
+
+    #[test]
+    fn sort_by() {
+        merge_sort::test::sort_by::<List<_, _>>(&Counting::default());
+    }
+
+    #[test]
+    fn sort_by_survives_panics() {
+        merge_sort::test::sort_by_survives_panics::<List<_, _>>(&Counting::default());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn par_sort_by() {
+        merge_sort::test::par_sort_by::<List<_>>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn par_sort_by_survives_panics() {
+        merge_sort::test::par_sort_by_survives_panics::<List<_>>();
+    }
 }
 
+#[cfg(test)]
 mod test_from_book {
     use super::List;
 
@@ -274,9 +876,9 @@ mod test_from_book {
         assert_eq!(list.peek(), Some(&3));
         assert_eq!(list.peek_mut(), Some(&mut 3));
 
-        list.peek_mut().map(|value| {
+        if let Some(value) = list.peek_mut() {
             *value = 42
-        });
+        }
 
         assert_eq!(list.peek(), Some(&42));
         assert_eq!(list.pop(), Some(42));
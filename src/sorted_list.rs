@@ -0,0 +1,324 @@
+use core::cell::Ref;
+use core::fmt;
+use core::ops::{Bound, RangeBounds};
+
+use crate::allocator::{Allocator, Global};
+use crate::deque_book::{self, List};
+
+/// A `deque_book::List` kept in ascending order, e.g. a queue of timers.
+///
+/// Equal elements stay in insertion order: `insert` puts an element after
+/// any equal ones, and `pop_min` and `remove` take the earliest of them.
+/// Most operations walk the list, so they're O(n); the ends are O(1).
+pub struct SortedList<T, A: Allocator + Clone = Global> {
+    list: List<T, A>,
+}
+
+impl<T: Ord> SortedList<T> {
+    pub fn new() -> Self {
+        SortedList::new_in(Global)
+    }
+}
+
+impl<T: Ord, A: Allocator + Clone> SortedList<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        SortedList {
+            list: List::new_in(alloc),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.peek_front().is_none()
+    }
+
+    /// Inserts `elem` after every element that isn't greater than it. The
+    /// search starts at the back, so adding something no smaller than most
+    /// of the list, like a later deadline, is quick.
+    pub fn insert(&mut self, elem: T) {
+        self.list.insert_by(elem, |prev, elem| prev <= elem);
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        self.list.pop_back()
+    }
+
+    pub fn peek_min(&self) -> Option<Ref<'_, T>> {
+        self.list.peek_front()
+    }
+
+    pub fn peek_max(&self) -> Option<Ref<'_, T>> {
+        self.list.peek_back()
+    }
+
+    /// Removes and returns the first element equal to `elem`, if any. The
+    /// search stops as soon as it passes where `elem` would be.
+    pub fn remove(&mut self, elem: &T) -> Option<T> {
+        self.list.remove_by(|other| other.cmp(elem))
+    }
+
+    /// Iterates over the elements in ascending order.
+    pub fn iter(&self) -> deque_book::Iter<'_, T, A> {
+        self.list.iter()
+    }
+
+    /// Iterates, in ascending order, over the elements within `range`. Both
+    /// ends are found by walking in from that end of the list.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Range<'_, T, R, A> {
+        Range {
+            iter: self.list.iter(),
+            range,
+            done: false,
+        }
+    }
+
+    /// Moves every element of `other` into this list in one linear pass,
+    /// relinking nodes rather than copying. Elements of this list go before
+    /// equal ones from `other`.
+    ///
+    /// If a comparison panics, no element is lost: each list keeps whatever
+    /// hadn't been merged yet, and this one also has everything that had.
+    pub fn merge(&mut self, other: &mut Self) {
+        self.list.merge_by(&mut other.list, |theirs, ours| theirs < ours);
+    }
+}
+
+impl<T: Ord> Default for SortedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for SortedList<T, A> {
+    fn clone(&self) -> Self {
+        SortedList {
+            list: self.list.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator + Clone> fmt::Debug for SortedList<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.list, f)
+    }
+}
+
+/// Drains the list in ascending order, or descending from the back.
+impl<T, A: Allocator + Clone> IntoIterator for SortedList<T, A> {
+    type Item = T;
+    type IntoIter = deque_book::IntoIter<T, A>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+}
+
+/********** Range **********/
+
+pub struct Range<'a, T, R, A: Allocator + Clone = Global> {
+    iter: deque_book::Iter<'a, T, A>,
+    range: R,
+    // Set once either end has walked out of the range, past which the
+    // other end has nothing left to find.
+    done: bool,
+}
+
+fn below<T: Ord>(start: Bound<&T>, elem: &T) -> bool {
+    match start {
+        Bound::Included(start) => elem < start,
+        Bound::Excluded(start) => elem <= start,
+        Bound::Unbounded => false,
+    }
+}
+
+fn above<T: Ord>(end: Bound<&T>, elem: &T) -> bool {
+    match end {
+        Bound::Included(end) => elem > end,
+        Bound::Excluded(end) => elem >= end,
+        Bound::Unbounded => false,
+    }
+}
+
+impl<'a, T: Ord, R: RangeBounds<T>, A: Allocator + Clone> Iterator for Range<'a, T, R, A> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Ref<'a, T>> {
+        while !self.done {
+            let elem = self.iter.next()?;
+            if above(self.range.end_bound(), &elem) {
+                self.done = true;
+            } else if !below(self.range.start_bound(), &elem) {
+                return Some(elem);
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T: Ord, R: RangeBounds<T>, A: Allocator + Clone> DoubleEndedIterator
+    for Range<'a, T, R, A>
+{
+    fn next_back(&mut self) -> Option<Ref<'a, T>> {
+        while !self.done {
+            let elem = self.iter.next_back()?;
+            if below(self.range.start_bound(), &elem) {
+                self.done = true;
+            } else if !above(self.range.end_bound(), &elem) {
+                return Some(elem);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SortedList;
+    use crate::panicky::{panics, Touchy};
+    use core::cmp::Ordering;
+    use std::vec::Vec;
+
+    // Ordered by deadline only, so ties show which one came first.
+    #[derive(Debug, Clone, Copy)]
+    struct Timer {
+        at: u32,
+        id: u32,
+    }
+
+    impl Ord for Timer {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.at.cmp(&other.at)
+        }
+    }
+
+    impl PartialOrd for Timer {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl PartialEq for Timer {
+        fn eq(&self, other: &Self) -> bool {
+            self.at == other.at
+        }
+    }
+
+    impl Eq for Timer {}
+
+    fn timers(ats: &[u32]) -> SortedList<Timer> {
+        let mut list = SortedList::new();
+        for (id, &at) in ats.iter().enumerate() {
+            list.insert(Timer { at, id: id as u32 });
+        }
+        list
+    }
+
+    fn ids(list: &SortedList<Timer>) -> Vec<(u32, u32)> {
+        list.iter().map(|t| (t.at, t.id)).collect()
+    }
+
+    #[test]
+    fn insert_keeps_order_and_ties_fifo() {
+        let list = timers(&[5, 1, 5, 9, 0, 5, 1]);
+        assert_eq!(
+            ids(&list),
+            [(0, 4), (1, 1), (1, 6), (5, 0), (5, 2), (5, 5), (9, 3)]
+        );
+        let back: Vec<u32> = list.iter().rev().map(|t| t.id).collect();
+        assert_eq!(back, [3, 5, 2, 0, 6, 1, 4]);
+    }
+
+    #[test]
+    fn ends() {
+        let mut list = timers(&[3, 1, 2, 1]);
+        assert_eq!(list.peek_min().map(|t| t.id), Some(1));
+        assert_eq!(list.peek_max().map(|t| t.id), Some(0));
+        assert_eq!(list.pop_min().map(|t| t.id), Some(1));
+        assert_eq!(list.pop_min().map(|t| t.id), Some(3));
+        assert_eq!(list.pop_max().map(|t| t.id), Some(0));
+        assert_eq!(list.pop_max().map(|t| t.id), Some(2));
+        assert!(list.is_empty());
+        assert!(list.pop_min().is_none());
+        assert!(list.peek_max().is_none());
+    }
+
+    #[test]
+    fn remove() {
+        let mut list = timers(&[4, 2, 4, 8, 6]);
+        let removed = list.remove(&Timer { at: 4, id: 99 });
+        assert_eq!(removed.map(|t| t.id), Some(0));
+        assert!(list.remove(&Timer { at: 5, id: 99 }).is_none());
+        assert!(list.remove(&Timer { at: 100, id: 99 }).is_none());
+        assert_eq!(list.remove(&Timer { at: 8, id: 99 }).map(|t| t.id), Some(3));
+        assert_eq!(list.remove(&Timer { at: 2, id: 99 }).map(|t| t.id), Some(1));
+        assert_eq!(ids(&list), [(4, 2), (6, 4)]);
+    }
+
+    #[test]
+    fn range() {
+        let mut list = SortedList::new();
+        for i in [7, 3, 9, 1, 5, 3, 8] {
+            list.insert(i);
+        }
+        let get = |v: Vec<core::cell::Ref<'_, i32>>| v.iter().map(|r| **r).collect::<Vec<_>>();
+        assert_eq!(get(list.range(3..8).collect()), [3, 3, 5, 7]);
+        assert_eq!(get(list.range(3..=8).rev().collect()), [8, 7, 5, 3, 3]);
+        assert_eq!(get(list.range(..4).collect()), [1, 3, 3]);
+        assert_eq!(get(list.range(6..).collect()), [7, 8, 9]);
+        assert_eq!(get(list.range(..).collect()), [1, 3, 3, 5, 7, 8, 9]);
+        assert!(list.range(4..5).next().is_none());
+        assert!(list.range(10..).next_back().is_none());
+
+        let mut both = list.range(2..9);
+        assert_eq!(both.next().map(|r| *r), Some(3));
+        assert_eq!(both.next_back().map(|r| *r), Some(8));
+        assert_eq!(both.next_back().map(|r| *r), Some(7));
+        assert_eq!(both.next().map(|r| *r), Some(3));
+        assert_eq!(both.next().map(|r| *r), Some(5));
+        assert!(both.next().is_none());
+        assert!(both.next_back().is_none());
+    }
+
+    #[test]
+    fn merge() {
+        let mut a = timers(&[1, 4, 4, 9]);
+        let mut b = SortedList::new();
+        for (at, id) in [(0, 10), (4, 11), (5, 12), (9, 13), (12, 14)] {
+            b.insert(Timer { at, id });
+        }
+        a.merge(&mut b);
+        assert!(b.is_empty());
+        assert_eq!(
+            ids(&a),
+            [(0, 10), (1, 0), (4, 1), (4, 2), (4, 11), (5, 12), (9, 3), (9, 13), (12, 14)]
+        );
+
+        let mut empty = SortedList::new();
+        a.merge(&mut empty);
+        empty.merge(&mut a);
+        assert!(a.is_empty());
+        assert_eq!(ids(&empty).len(), 9);
+    }
+
+    #[test]
+    fn merge_survives_panics() {
+        let mut a = SortedList::new();
+        let mut b = SortedList::new();
+        for i in [1, 4, 6] {
+            a.insert(Touchy(i));
+        }
+        for i in [2, 3, 5] {
+            b.insert(Touchy(i));
+        }
+        // Slip in an element that panics when compared.
+        b.list.push_back(Touchy(-1));
+        a.insert(Touchy(7));
+        assert!(panics(|| a.merge(&mut b)));
+        let mut all: Vec<i32> = a.list.iter().chain(b.list.iter()).map(|t| t.0).collect();
+        all.sort();
+        assert_eq!(all, [-1, 1, 2, 3, 4, 5, 6, 7]);
+    }
+}
@@ -0,0 +1,654 @@
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+// Heap-free versions of sll::List and deque_book::List. Nodes live in an
+// inline array of N slots and link to each other by index; unused slots are
+// chained into a free list. Pushing onto a full list hands the element back
+// as Err(elem) instead of allocating.
+
+const NIL: usize = usize::MAX;
+
+fn uninit_slots<T, const N: usize>() -> [MaybeUninit<T>; N] {
+    // SAFETY: an array of MaybeUninit is fine left uninitialized.
+    unsafe { MaybeUninit::uninit().assume_init() }
+}
+
+/********** StaticList **********/
+
+pub struct StaticList<T, const N: usize> {
+    elems: [MaybeUninit<T>; N],
+    next: [usize; N],
+    head: usize,
+    free: usize,
+    len: usize,
+    // MaybeUninit doesn't tell dropck that we drop the elements.
+    _owns: PhantomData<T>,
+}
+
+impl<T, const N: usize> StaticList<T, N> {
+    pub fn new() -> Self {
+        let mut next = [NIL; N];
+        for (i, link) in next.iter_mut().enumerate().take(N.saturating_sub(1)) {
+            *link = i + 1;
+        }
+        StaticList {
+            elems: uninit_slots(),
+            next,
+            head: NIL,
+            free: if N == 0 { NIL } else { 0 },
+            len: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    pub fn push(&mut self, elem: T) -> Result<(), T> {
+        if self.free == NIL {
+            return Err(elem);
+        }
+        let slot = self.free;
+        self.free = self.next[slot];
+        self.elems[slot].write(elem);
+        self.next[slot] = self.head;
+        self.head = slot;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.head == NIL {
+            return None;
+        }
+        let slot = self.head;
+        self.head = self.next[slot];
+        self.next[slot] = self.free;
+        self.free = slot;
+        self.len -= 1;
+        // SAFETY: slots reachable from head are initialized.
+        Some(unsafe { self.elems[slot].assume_init_read() })
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        if self.head == NIL {
+            None
+        } else {
+            Some(unsafe { self.elems[self.head].assume_init_ref() })
+        }
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        if self.head == NIL {
+            None
+        } else {
+            Some(unsafe { self.elems[self.head].assume_init_mut() })
+        }
+    }
+
+    pub fn iter(&self) -> StaticListIter<'_, T, N> {
+        StaticListIter {
+            list: self,
+            cur: self.head,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> StaticListIterMut<'_, T, N> {
+        StaticListIterMut {
+            cur: self.head,
+            elems: self.elems.as_mut_ptr(),
+            next: &self.next,
+            _list: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for StaticList<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T, const N: usize> Drop for StaticList<T, N> {
+    fn drop(&mut self) {
+        self.drop_elems();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T, const N: usize> Drop for StaticList<T, N> {
+    fn drop(&mut self) {
+        self.drop_elems();
+    }
+}
+
+impl<T, const N: usize> StaticList<T, N> {
+    fn drop_elems(&mut self) {
+        // Keeps popping during the unwind if an element's destructor panics,
+        // so the remaining elements still get dropped.
+        struct DropGuard<'a, T, const N: usize>(&'a mut StaticList<T, N>);
+
+        impl<T, const N: usize> Drop for DropGuard<'_, T, N> {
+            fn drop(&mut self) {
+                while self.0.pop().is_some() {}
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.pop().is_some() {}
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for StaticList<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct StaticListIter<'a, T, const N: usize> {
+    list: &'a StaticList<T, N>,
+    cur: usize,
+}
+
+impl<'a, T, const N: usize> Iterator for StaticListIter<'a, T, N> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur == NIL {
+            return None;
+        }
+        let slot = self.cur;
+        self.cur = self.list.next[slot];
+        Some(unsafe { self.list.elems[slot].assume_init_ref() })
+    }
+}
+
+pub struct StaticListIterMut<'a, T, const N: usize> {
+    cur: usize,
+    elems: *mut MaybeUninit<T>,
+    next: &'a [usize; N],
+    _list: PhantomData<&'a mut T>,
+}
+
+// The raw element pointer opts these out of Send and Sync; they behave like
+// the &mut T they hand out.
+unsafe impl<T: Send, const N: usize> Send for StaticListIterMut<'_, T, N> {}
+unsafe impl<T: Sync, const N: usize> Sync for StaticListIterMut<'_, T, N> {}
+
+impl<'a, T, const N: usize> Iterator for StaticListIterMut<'a, T, N> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur == NIL {
+            return None;
+        }
+        let slot = self.cur;
+        self.cur = self.next[slot];
+        // SAFETY: each live slot is visited once, so the &mut never aliases.
+        Some(unsafe { (*self.elems.add(slot)).assume_init_mut() })
+    }
+}
+
+pub struct StaticListIntoIter<T, const N: usize>(StaticList<T, N>);
+
+impl<T, const N: usize> Iterator for StaticListIntoIter<T, N> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for StaticList<T, N> {
+    type Item = T;
+    type IntoIter = StaticListIntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        StaticListIntoIter(self)
+    }
+}
+
+/********** StaticDeque **********/
+
+pub struct StaticDeque<T, const N: usize> {
+    elems: [MaybeUninit<T>; N],
+    next: [usize; N],
+    prev: [usize; N],
+    head: usize,
+    tail: usize,
+    // Free slots are chained through `next` only.
+    free: usize,
+    len: usize,
+    _owns: PhantomData<T>,
+}
+
+impl<T, const N: usize> StaticDeque<T, N> {
+    pub fn new() -> Self {
+        let mut next = [NIL; N];
+        for (i, link) in next.iter_mut().enumerate().take(N.saturating_sub(1)) {
+            *link = i + 1;
+        }
+        StaticDeque {
+            elems: uninit_slots(),
+            next,
+            prev: [NIL; N],
+            head: NIL,
+            tail: NIL,
+            free: if N == 0 { NIL } else { 0 },
+            len: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    fn take_slot(&mut self, elem: T) -> Result<usize, T> {
+        if self.free == NIL {
+            return Err(elem);
+        }
+        let slot = self.free;
+        self.free = self.next[slot];
+        self.elems[slot].write(elem);
+        self.len += 1;
+        Ok(slot)
+    }
+
+    fn release_slot(&mut self, slot: usize) -> T {
+        self.next[slot] = self.free;
+        self.prev[slot] = NIL;
+        self.free = slot;
+        self.len -= 1;
+        unsafe { self.elems[slot].assume_init_read() }
+    }
+
+    pub fn push_front(&mut self, elem: T) -> Result<(), T> {
+        let new_head = self.take_slot(elem)?;
+        self.prev[new_head] = NIL;
+        self.next[new_head] = self.head;
+        match self.head {
+            NIL => self.tail = new_head,
+            old_head => self.prev[old_head] = new_head,
+        }
+        self.head = new_head;
+        Ok(())
+    }
+
+    pub fn push_back(&mut self, elem: T) -> Result<(), T> {
+        let new_tail = self.take_slot(elem)?;
+        self.next[new_tail] = NIL;
+        self.prev[new_tail] = self.tail;
+        match self.tail {
+            NIL => self.head = new_tail,
+            old_tail => self.next[old_tail] = new_tail,
+        }
+        self.tail = new_tail;
+        Ok(())
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.head == NIL {
+            return None;
+        }
+        let old_head = self.head;
+        self.head = self.next[old_head];
+        match self.head {
+            NIL => self.tail = NIL,
+            new_head => self.prev[new_head] = NIL,
+        }
+        Some(self.release_slot(old_head))
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.tail == NIL {
+            return None;
+        }
+        let old_tail = self.tail;
+        self.tail = self.prev[old_tail];
+        match self.tail {
+            NIL => self.head = NIL,
+            new_tail => self.next[new_tail] = NIL,
+        }
+        Some(self.release_slot(old_tail))
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        self.get(self.head)
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        self.get(self.tail)
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.head)
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(self.tail)
+    }
+
+    fn get(&self, slot: usize) -> Option<&T> {
+        if slot == NIL {
+            None
+        } else {
+            Some(unsafe { self.elems[slot].assume_init_ref() })
+        }
+    }
+
+    fn get_mut(&mut self, slot: usize) -> Option<&mut T> {
+        if slot == NIL {
+            None
+        } else {
+            Some(unsafe { self.elems[slot].assume_init_mut() })
+        }
+    }
+
+    pub fn iter(&self) -> StaticDequeIter<'_, T, N> {
+        StaticDequeIter {
+            deque: self,
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> StaticDequeIterMut<'_, T, N> {
+        StaticDequeIterMut {
+            front: self.head,
+            back: self.tail,
+            len: self.len,
+            elems: self.elems.as_mut_ptr(),
+            next: &self.next,
+            prev: &self.prev,
+            _deque: PhantomData,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for StaticDeque<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T, const N: usize> Drop for StaticDeque<T, N> {
+    fn drop(&mut self) {
+        self.drop_elems();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T, const N: usize> Drop for StaticDeque<T, N> {
+    fn drop(&mut self) {
+        self.drop_elems();
+    }
+}
+
+impl<T, const N: usize> StaticDeque<T, N> {
+    fn drop_elems(&mut self) {
+        // Same as StaticList.
+        struct DropGuard<'a, T, const N: usize>(&'a mut StaticDeque<T, N>);
+
+        impl<T, const N: usize> Drop for DropGuard<'_, T, N> {
+            fn drop(&mut self) {
+                while self.0.pop_front().is_some() {}
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.pop_front().is_some() {}
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for StaticDeque<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// The double-ended iterators count down `len` so the two ends stop when they
+// meet instead of walking past each other.
+pub struct StaticDequeIter<'a, T, const N: usize> {
+    deque: &'a StaticDeque<T, N>,
+    front: usize,
+    back: usize,
+    len: usize,
+}
+
+impl<'a, T, const N: usize> Iterator for StaticDequeIter<'a, T, N> {
+    type Item = &'a T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.front;
+        self.front = self.deque.next[slot];
+        self.deque.get(slot)
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for StaticDequeIter<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.back;
+        self.back = self.deque.prev[slot];
+        self.deque.get(slot)
+    }
+}
+
+pub struct StaticDequeIterMut<'a, T, const N: usize> {
+    front: usize,
+    back: usize,
+    len: usize,
+    elems: *mut MaybeUninit<T>,
+    next: &'a [usize; N],
+    prev: &'a [usize; N],
+    _deque: PhantomData<&'a mut T>,
+}
+
+unsafe impl<T: Send, const N: usize> Send for StaticDequeIterMut<'_, T, N> {}
+unsafe impl<T: Sync, const N: usize> Sync for StaticDequeIterMut<'_, T, N> {}
+
+impl<'a, T, const N: usize> Iterator for StaticDequeIterMut<'a, T, N> {
+    type Item = &'a mut T;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.front;
+        self.front = self.next[slot];
+        Some(unsafe { (*self.elems.add(slot)).assume_init_mut() })
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for StaticDequeIterMut<'_, T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let slot = self.back;
+        self.back = self.prev[slot];
+        Some(unsafe { (*self.elems.add(slot)).assume_init_mut() })
+    }
+}
+
+pub struct StaticDequeIntoIter<T, const N: usize>(StaticDeque<T, N>);
+
+impl<T, const N: usize> Iterator for StaticDequeIntoIter<T, N> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for StaticDequeIntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T, const N: usize> IntoIterator for StaticDeque<T, N> {
+    type Item = T;
+    type IntoIter = StaticDequeIntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        StaticDequeIntoIter(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{StaticDeque, StaticList};
+    use crate::panicky::{panics, Bomb};
+    use core::cell::Cell;
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    #[test]
+    fn list_basics() {
+        let mut list: StaticList<i32, 3> = StaticList::new();
+        assert_eq!(list.pop(), None);
+        assert_eq!(list.peek(), None);
+
+        list.push(1).unwrap();
+        list.push(2).unwrap();
+        list.push(3).unwrap();
+        assert!(list.is_full());
+        assert_eq!(list.push(4), Err(4));
+
+        assert_eq!(list.peek(), Some(&3));
+        *list.peek_mut().unwrap() = 30;
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![30, 2, 1]);
+
+        for elem in list.iter_mut() {
+            *elem *= 10;
+        }
+        assert_eq!(list.pop(), Some(300));
+        assert_eq!(list.pop(), Some(20));
+
+        // Freed slots get reused.
+        list.push(5).unwrap();
+        list.push(6).unwrap();
+        assert_eq!(list.push(7), Err(7));
+        assert_eq!(list.into_iter().collect::<Vec<_>>(), vec![6, 5, 10]);
+    }
+
+    #[test]
+    fn zero_capacity() {
+        let mut list: StaticList<i32, 0> = StaticList::new();
+        assert_eq!(list.push(1), Err(1));
+        assert_eq!(list.pop(), None);
+
+        let mut deque: StaticDeque<i32, 0> = StaticDeque::new();
+        assert_eq!(deque.push_front(1), Err(1));
+        assert_eq!(deque.push_back(2), Err(2));
+        assert_eq!(deque.pop_back(), None);
+    }
+
+    #[test]
+    fn deque_basics() {
+        let mut list: StaticDeque<i32, 4> = StaticDeque::new();
+
+        // Check empty list behaves right
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+
+        list.push_front(1).unwrap();
+        list.push_front(2).unwrap();
+        list.push_back(3).unwrap();
+        list.push_back(4).unwrap();
+        assert_eq!(list.push_back(5), Err(5));
+        assert_eq!(list.push_front(6), Err(6));
+
+        assert_eq!(list.peek_front(), Some(&2));
+        assert_eq!(list.peek_back(), Some(&4));
+        *list.peek_back_mut().unwrap() = 40;
+        *list.peek_front_mut().unwrap() = 20;
+
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![20, 1, 3, 40]);
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), vec![40, 3, 1, 20]);
+
+        assert_eq!(list.pop_front(), Some(20));
+        assert_eq!(list.pop_back(), Some(40));
+        list.push_back(7).unwrap();
+        list.push_front(8).unwrap();
+
+        let mut iter = list.iter_mut();
+        *iter.next().unwrap() += 100;
+        *iter.next_back().unwrap() += 100;
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![108, 1, 3, 107]);
+
+        let mut iter = list.into_iter();
+        assert_eq!(iter.next(), Some(108));
+        assert_eq!(iter.next_back(), Some(107));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next_back(), Some(3));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn drops_remaining_elements() {
+        let elem = Rc::new(());
+        {
+            let mut list: StaticList<Rc<()>, 4> = StaticList::new();
+            let mut deque: StaticDeque<Rc<()>, 4> = StaticDeque::new();
+            for _ in 0..3 {
+                list.push(elem.clone()).unwrap();
+                deque.push_back(elem.clone()).unwrap();
+            }
+            list.pop();
+            deque.pop_front();
+            assert_eq!(Rc::strong_count(&elem), 5);
+        }
+        assert_eq!(Rc::strong_count(&elem), 1);
+    }
+
+    #[test]
+    fn drop_survives_panicking_destructor() {
+        let drops = Cell::new(0);
+        let mut list: StaticList<Bomb, 4> = StaticList::new();
+        let mut deque: StaticDeque<Bomb, 4> = StaticDeque::new();
+        for i in 0..4 {
+            let bomb = |drops| if i == 1 { Bomb::armed(drops) } else { Bomb::new(drops) };
+            assert!(list.push(bomb(&drops)).is_ok());
+            assert!(deque.push_back(bomb(&drops)).is_ok());
+        }
+        assert!(panics(move || drop(list)));
+        assert_eq!(drops.get(), 4);
+        assert!(panics(move || drop(deque)));
+        assert_eq!(drops.get(), 8);
+    }
+}
@@ -0,0 +1,281 @@
+use alloc::boxed::Box;
+use core::marker::PhantomData;
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use crate::atomic::{AtomicPtr, Ordering::SeqCst};
+use crate::reclaim::Reclaimer;
+
+// A lock-free stack (Treiber stack). Same shape as sll::List, but the head is
+// swapped in with a CAS so any number of threads can push and pop at once.
+pub struct Stack<T> {
+    head: AtomicPtr<Node<T>>,
+    reclaim: Reclaimer,
+    _owns: PhantomData<T>,
+}
+
+struct Node<T> {
+    // Moved out by whoever wins the pop, so the node itself never drops it.
+    elem: ManuallyDrop<T>,
+    next: *mut Node<T>,
+}
+
+unsafe impl<T: Send> Send for Stack<T> {}
+unsafe impl<T: Send> Sync for Stack<T> {}
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Stack {
+            head: AtomicPtr::new(ptr::null_mut()),
+            reclaim: Reclaimer::new(),
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn push(&self, elem: T) {
+        let new_node = Box::into_raw(Box::new(Node {
+            elem: ManuallyDrop::new(elem),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(SeqCst);
+        loop {
+            // SAFETY: new_node is ours until the CAS publishes it.
+            unsafe { (*new_node).next = head };
+            match self.head.compare_exchange(head, new_node, SeqCst, SeqCst) {
+                Ok(_) => return,
+                Err(cur) => head = cur,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let guard = self.reclaim.enter();
+        let mut head = self.head.load(SeqCst);
+        loop {
+            if head.is_null() {
+                return None;
+            }
+            // SAFETY: the guard keeps head alive even if another thread pops it
+            // first; in that case the CAS below fails and we retry.
+            let next = unsafe { (*head).next };
+            match self.head.compare_exchange(head, next, SeqCst, SeqCst) {
+                Ok(_) => unsafe {
+                    let elem = ptr::read(&(*head).elem);
+                    guard.retire(head);
+                    return Some(ManuallyDrop::into_inner(elem));
+                },
+                Err(cur) => head = cur,
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(SeqCst).is_null()
+    }
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        let mut rest = Chain(self.head.load(SeqCst));
+        rest.free();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        let mut rest = Chain(self.head.load(SeqCst));
+        rest.free();
+    }
+}
+
+// The nodes from a pointer onwards. If an element's destructor panics while
+// `free` runs, dropping the Chain during the unwind frees the rest.
+struct Chain<T>(*mut Node<T>);
+
+impl<T> Chain<T> {
+    fn free(&mut self) {
+        while !self.0.is_null() {
+            let mut node = unsafe { Box::from_raw(self.0) };
+            self.0 = node.next;
+            unsafe { ManuallyDrop::drop(&mut node.elem) };
+        }
+    }
+}
+
+impl<T> Drop for Chain<T> {
+    fn drop(&mut self) {
+        self.free();
+    }
+}
+
+#[cfg(all(test, not(feature = "loom")))]
+mod test {
+    use super::Stack;
+    use crate::panicky::{panics, Bomb};
+    use core::cell::Cell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn basics() {
+        let stack = Stack::new();
+        assert!(stack.is_empty());
+        assert_eq!(stack.pop(), None);
+
+        stack.push(1);
+        stack.push(2);
+        stack.push(3);
+        assert!(!stack.is_empty());
+
+        assert_eq!(stack.pop(), Some(3));
+        assert_eq!(stack.pop(), Some(2));
+
+        stack.push(4);
+        assert_eq!(stack.pop(), Some(4));
+        assert_eq!(stack.pop(), Some(1));
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn hammer() {
+        const THREADS: usize = 8;
+        const PER_THREAD: usize = 10_000;
+
+        let stack = Arc::new(Stack::new());
+        let seen: Arc<Vec<AtomicUsize>> =
+            Arc::new((0..THREADS * PER_THREAD).map(|_| AtomicUsize::new(0)).collect());
+
+        let mut handles = vec![];
+        for t in 0..THREADS {
+            let stack = stack.clone();
+            let seen = seen.clone();
+            handles.push(thread::spawn(move || {
+                for i in 0..PER_THREAD {
+                    stack.push(t * PER_THREAD + i);
+                    if i % 2 == 1 {
+                        for _ in 0..2 {
+                            if let Some(n) = stack.pop() {
+                                seen[n].fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                }
+            }));
+        }
+        for h in handles {
+            h.join().unwrap();
+        }
+        while let Some(n) = stack.pop() {
+            seen[n].fetch_add(1, Ordering::Relaxed);
+        }
+        assert!(seen.iter().all(|n| n.load(Ordering::Relaxed) == 1));
+    }
+
+    #[test]
+    fn drops_everything() {
+        struct Counted(Arc<AtomicUsize>);
+        impl Drop for Counted {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let stack = Arc::new(Stack::new());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let stack = stack.clone();
+                let drops = drops.clone();
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        stack.push(Counted(drops.clone()));
+                    }
+                    for _ in 0..500 {
+                        stack.pop();
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap();
+        }
+        assert_eq!(drops.load(Ordering::Relaxed), 2000);
+        drop(stack);
+        assert_eq!(drops.load(Ordering::Relaxed), 4000);
+    }
+
+    #[test]
+    fn drop_survives_panicking_destructor() {
+        let drops = Cell::new(0);
+        let stack = Stack::new();
+        for i in 0..5 {
+            stack.push(if i == 2 { Bomb::armed(&drops) } else { Bomb::new(&drops) });
+        }
+        assert!(panics(move || drop(stack)));
+        assert_eq!(drops.get(), 5);
+    }
+}
+
+#[cfg(all(test, feature = "loom"))]
+mod loom_test {
+    use super::Stack;
+    use loom::sync::Arc;
+    use loom::thread;
+    use std::vec::Vec;
+
+    #[test]
+    fn push_pop_race() {
+        loom::model(|| {
+            let stack = Arc::new(Stack::new());
+            let s1 = stack.clone();
+            let s2 = stack.clone();
+
+            let t1 = thread::spawn(move || {
+                s1.push(1);
+                s1.pop()
+            });
+            let t2 = thread::spawn(move || {
+                s2.push(2);
+                s2.pop()
+            });
+
+            let mut got = vec![t1.join().unwrap(), t2.join().unwrap()];
+            while let Some(n) = stack.pop() {
+                got.push(Some(n));
+            }
+            let mut got: Vec<_> = got.into_iter().flatten().collect();
+            got.sort();
+            assert_eq!(got, vec![1, 2]);
+        });
+    }
+
+    #[test]
+    fn concurrent_pops() {
+        loom::model(|| {
+            let stack = Arc::new(Stack::new());
+            stack.push(1);
+            stack.push(2);
+            let s1 = stack.clone();
+            let s2 = stack.clone();
+
+            let t1 = thread::spawn(move || s1.pop());
+            let t2 = thread::spawn(move || s2.pop());
+
+            let mut got = vec![t1.join().unwrap().unwrap(), t2.join().unwrap().unwrap()];
+            got.sort();
+            assert_eq!(got, vec![1, 2]);
+            assert!(stack.is_empty());
+        });
+    }
+}
@@ -0,0 +1,649 @@
+// A persistent sequence in the style of an RRB tree: elements sit in
+// Rc-shared chunks of up to M, under branches of up to M children that keep
+// a table of their children's sizes, so indexing never assumes the tree is
+// full. Like a B-tree, every leaf is at the same depth and every node but
+// the root is at least half full, which keeps the depth logarithmic.
+//
+// Concatenation merges the two trees along the seam: it walks down the
+// taller tree's edge to the shorter one's height, merges the two nodes
+// there, and splits the merged node or its ancestors wherever they
+// overflow. Splitting cuts along the path to the index and concatenates the
+// pieces back up. Both copy O(log n) nodes and share everything else; only
+// the chunks on the seam or the cut have their elements cloned.
+
+use alloc::rc::Rc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::FromIterator;
+use core::slice;
+
+use crate::shared_list;
+
+// M is the branching factor. It's only a parameter so the tests can build
+// deep trees with a small one as well as checking the real one.
+pub struct Vector<T, const M: usize = 32> {
+    root: Option<Rc<Node<T>>>,
+    // Levels of branches above the leaves.
+    height: usize,
+    len: usize,
+}
+
+enum Node<T> {
+    Leaf(Vec<T>),
+    Branch(Branch<T>),
+}
+
+struct Branch<T> {
+    kids: Vec<Rc<Node<T>>>,
+    // sizes[i] is how many elements are in kids[..=i].
+    sizes: Vec<usize>,
+}
+
+// A root and its height.
+type Tree<T> = (Rc<Node<T>>, usize);
+
+impl<T> Node<T> {
+    fn branch(kids: Vec<Rc<Node<T>>>) -> Node<T> {
+        let sizes = kids
+            .iter()
+            .scan(0, |total, kid| {
+                *total += kid.len();
+                Some(*total)
+            })
+            .collect();
+        Node::Branch(Branch { kids, sizes })
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Node::Leaf(elems) => elems.len(),
+            Node::Branch(branch) => *branch.sizes.last().unwrap(),
+        }
+    }
+}
+
+impl<T> Branch<T> {
+    // Which child holds `index`, and where it is within that child.
+    fn find(&self, index: usize) -> (usize, usize) {
+        let i = self.sizes.partition_point(|&size| size <= index);
+        let before = if i == 0 { 0 } else { self.sizes[i - 1] };
+        (i, index - before)
+    }
+}
+
+// Shares `items` out over as few nodes as will hold them, as evenly as
+// possible. Anything over M ends up with every node at least half full.
+fn even_chunks<E, const M: usize>(items: Vec<E>) -> Vec<Vec<E>> {
+    let len = items.len();
+    let count = len.div_ceil(M);
+    let mut items = items.into_iter();
+    (0..count)
+        .map(|i| {
+            let size = len / count + usize::from(i < len % count);
+            items.by_ref().take(size).collect()
+        })
+        .collect()
+}
+
+fn branches<T, const M: usize>(kids: Vec<Rc<Node<T>>>) -> Vec<Node<T>> {
+    even_chunks::<_, M>(kids).into_iter().map(Node::branch).collect()
+}
+
+impl<T> Vector<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T, const M: usize> Vector<T, M> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        let mut node = &**self.root.as_ref()?;
+        let mut index = index;
+        loop {
+            match node {
+                Node::Leaf(elems) => return elems.get(index),
+                Node::Branch(branch) => {
+                    let (i, rest) = branch.find(index);
+                    node = &branch.kids[i];
+                    index = rest;
+                }
+            }
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let root = self.root.as_slice();
+        Iter {
+            front: vec![root.iter()],
+            front_leaf: [].iter(),
+            back: vec![root.iter()],
+            back_leaf: [].iter(),
+            len: self.len,
+        }
+    }
+
+    fn from_tree(tree: Option<Tree<T>>) -> Self {
+        match tree {
+            Some((root, height)) => Vector {
+                len: root.len(),
+                root: Some(root),
+                height,
+            },
+            None => Vector::default(),
+        }
+    }
+}
+
+// Puts one or two nodes of the given height under a root, adding a level
+// for two, and drops any levels that have a single child.
+fn top<T>(mut nodes: Vec<Node<T>>, height: usize) -> Tree<T> {
+    let (mut root, mut height) = if nodes.len() == 1 {
+        (Rc::new(nodes.pop().unwrap()), height)
+    } else {
+        let kids = nodes.into_iter().map(Rc::new).collect();
+        (Rc::new(Node::branch(kids)), height + 1)
+    };
+    while let Node::Branch(Branch { kids, .. }) = &*root {
+        if kids.len() > 1 {
+            break;
+        }
+        root = kids[0].clone();
+        height -= 1;
+    }
+    (root, height)
+}
+
+impl<T: Clone, const M: usize> Vector<T, M> {
+    // A new version with `elem` added at the end; this one is unchanged.
+    pub fn push_back(&self, elem: T) -> Self {
+        let leaf = Rc::new(Node::Leaf(vec![elem]));
+        self.concat(&Vector::from_tree(Some((leaf, 0))))
+    }
+
+    // A new version with the element at `index` replaced; this one is
+    // unchanged.
+    //
+    // Panics if `index` is out of bounds.
+    pub fn set(&self, index: usize, elem: T) -> Self {
+        assert!(index < self.len, "set index out of bounds");
+        let root = set(self.root.as_ref().unwrap(), index, elem);
+        Vector {
+            root: Some(Rc::new(root)),
+            height: self.height,
+            len: self.len,
+        }
+    }
+
+    // This vector followed by `other`, as a new version. O(log n).
+    pub fn concat(&self, other: &Self) -> Self {
+        match (&self.root, &other.root) {
+            (Some(left), Some(right)) => {
+                let nodes = join::<T, M>((left, self.height), (right, other.height));
+                let height = self.height.max(other.height);
+                Vector::from_tree(Some(top(nodes, height)))
+            }
+            (None, _) => other.clone(),
+            (_, None) => self.clone(),
+        }
+    }
+
+    // The first `index` elements and the rest, as new versions. O(log n).
+    //
+    // Panics if `index` is greater than the length.
+    pub fn split_at(&self, index: usize) -> (Self, Self) {
+        assert!(index <= self.len, "split index out of bounds");
+        if index == 0 {
+            return (Vector::default(), self.clone());
+        }
+        if index == self.len {
+            return (self.clone(), Vector::default());
+        }
+        let (left, right) = split::<T, M>((self.root.as_ref().unwrap(), self.height), index);
+        (Vector::from_tree(left), Vector::from_tree(right))
+    }
+}
+
+fn set<T: Clone>(node: &Node<T>, index: usize, elem: T) -> Node<T> {
+    match node {
+        Node::Leaf(elems) => {
+            let mut elems = elems.clone();
+            elems[index] = elem;
+            Node::Leaf(elems)
+        }
+        Node::Branch(branch) => {
+            let (i, rest) = branch.find(index);
+            let mut kids = branch.kids.clone();
+            kids[i] = Rc::new(set(&kids[i], rest, elem));
+            Node::Branch(Branch {
+                kids,
+                sizes: branch.sizes.clone(),
+            })
+        }
+    }
+}
+
+// Merges two trees into one or two nodes as tall as the taller of them.
+fn join<T: Clone, const M: usize>(
+    left: (&Rc<Node<T>>, usize),
+    right: (&Rc<Node<T>>, usize),
+) -> Vec<Node<T>> {
+    let ((left, left_height), (right, right_height)) = (left, right);
+    match (&**left, &**right) {
+        (Node::Leaf(ours), Node::Leaf(theirs)) => {
+            let elems = ours.iter().chain(theirs).cloned().collect();
+            even_chunks::<_, M>(elems).into_iter().map(Node::Leaf).collect()
+        }
+        (Node::Branch(ours), _) if left_height > right_height => {
+            let (last, kids) = ours.kids.split_last().unwrap();
+            let seam = join::<T, M>((last, left_height - 1), (right, right_height));
+            let mut kids = kids.to_vec();
+            kids.extend(seam.into_iter().map(Rc::new));
+            branches::<T, M>(kids)
+        }
+        (_, Node::Branch(theirs)) if right_height > left_height => {
+            let (first, rest) = theirs.kids.split_first().unwrap();
+            let seam = join::<T, M>((left, left_height), (first, right_height - 1));
+            let mut kids: Vec<_> = seam.into_iter().map(Rc::new).collect();
+            kids.extend_from_slice(rest);
+            branches::<T, M>(kids)
+        }
+        (Node::Branch(ours), Node::Branch(theirs)) => {
+            let (last, ours) = ours.kids.split_last().unwrap();
+            let (first, theirs) = theirs.kids.split_first().unwrap();
+            let height = left_height - 1;
+            let seam = join::<T, M>((last, height), (first, height));
+            let mut kids = ours.to_vec();
+            kids.extend(seam.into_iter().map(Rc::new));
+            kids.extend_from_slice(theirs);
+            branches::<T, M>(kids)
+        }
+        _ => unreachable!("leaves at different depths"),
+    }
+}
+
+fn join_trees<T: Clone, const M: usize>(
+    left: Option<Tree<T>>,
+    right: Option<Tree<T>>,
+) -> Option<Tree<T>> {
+    match (left, right) {
+        (Some(left), Some(right)) => {
+            let height = left.1.max(right.1);
+            Some(top(join::<T, M>((&left.0, left.1), (&right.0, right.1)), height))
+        }
+        (left, None) => left,
+        (None, right) => right,
+    }
+}
+
+// The children in `kids` as a tree of the given height, if there are any.
+fn fragment<T>(kids: &[Rc<Node<T>>], height: usize) -> Option<Tree<T>> {
+    match kids {
+        [] => None,
+        [kid] => Some((kid.clone(), height - 1)),
+        kids => Some((Rc::new(Node::branch(kids.to_vec())), height)),
+    }
+}
+
+// Splits a tree before `index`, which is inside it.
+fn split<T: Clone, const M: usize>(
+    tree: (&Rc<Node<T>>, usize),
+    index: usize,
+) -> (Option<Tree<T>>, Option<Tree<T>>) {
+    let (node, height) = tree;
+    match &**node {
+        Node::Leaf(elems) => {
+            let leaf = |elems: &[T]| match elems {
+                [] => None,
+                elems => Some((Rc::new(Node::Leaf(elems.to_vec())), 0)),
+            };
+            (leaf(&elems[..index]), leaf(&elems[index..]))
+        }
+        Node::Branch(branch) => {
+            let (i, rest) = branch.find(index);
+            let (left, right) = if rest == 0 {
+                (None, Some((branch.kids[i].clone(), height - 1)))
+            } else {
+                split::<T, M>((&branch.kids[i], height - 1), rest)
+            };
+            (
+                join_trees::<T, M>(fragment(&branch.kids[..i], height), left),
+                join_trees::<T, M>(right, fragment(&branch.kids[i + 1..], height)),
+            )
+        }
+    }
+}
+
+// Cloning a version only bumps the root's refcount.
+impl<T, const M: usize> Clone for Vector<T, M> {
+    fn clone(&self) -> Self {
+        Vector {
+            root: self.root.clone(),
+            height: self.height,
+            len: self.len,
+        }
+    }
+}
+
+impl<T, const M: usize> Default for Vector<T, M> {
+    fn default() -> Self {
+        Vector {
+            root: None,
+            height: 0,
+            len: 0,
+        }
+    }
+}
+
+impl<T: PartialEq, const M: usize> PartialEq for Vector<T, M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq, const M: usize> Eq for Vector<T, M> {}
+
+impl<T: fmt::Debug, const M: usize> fmt::Debug for Vector<T, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Builds the tree bottom up with every node as full as it can evenly be.
+impl<T, const M: usize> FromIterator<T> for Vector<T, M> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let elems: Vec<T> = iter.into_iter().collect();
+        let mut nodes: Vec<Node<T>> = even_chunks::<_, M>(elems)
+            .into_iter()
+            .map(Node::Leaf)
+            .collect();
+        if nodes.is_empty() {
+            return Vector::default();
+        }
+        let mut height = 0;
+        while nodes.len() > 2 {
+            nodes = branches::<T, M>(nodes.into_iter().map(Rc::new).collect());
+            height += 1;
+        }
+        Vector::from_tree(Some(top(nodes, height)))
+    }
+}
+
+// The list's head becomes the first element.
+impl<T: Clone> From<&shared_list::List<T>> for Vector<T> {
+    fn from(list: &shared_list::List<T>) -> Self {
+        list.iter().cloned().collect()
+    }
+}
+
+impl<T: Clone> From<&Vector<T>> for shared_list::List<T> {
+    fn from(vector: &Vector<T>) -> Self {
+        vector
+            .iter()
+            .rev()
+            .fold(shared_list::List::new(), |list, elem| list.append(elem.clone()))
+    }
+}
+
+/********** Iterator **********/
+
+pub struct Iter<'a, T> {
+    // The children still to visit at each level on the way down to the
+    // current leaf, from either end.
+    front: Vec<slice::Iter<'a, Rc<Node<T>>>>,
+    front_leaf: slice::Iter<'a, T>,
+    back: Vec<slice::Iter<'a, Rc<Node<T>>>>,
+    back_leaf: slice::Iter<'a, T>,
+    // Both ends walk the whole tree; this is what stops them meeting.
+    len: usize,
+}
+
+type Step<'a, E> = fn(&mut slice::Iter<'a, E>) -> Option<&'a E>;
+
+// The next leaf from one end. There must be one.
+fn next_leaf<'a, T>(
+    stack: &mut Vec<slice::Iter<'a, Rc<Node<T>>>>,
+    step: Step<'a, Rc<Node<T>>>,
+) -> slice::Iter<'a, T> {
+    loop {
+        match step(stack.last_mut().unwrap()) {
+            Some(node) => match &**node {
+                Node::Leaf(elems) => return elems.iter(),
+                Node::Branch(branch) => stack.push(branch.kids.iter()),
+            },
+            None => {
+                stack.pop();
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        loop {
+            if let Some(elem) = self.front_leaf.next() {
+                return Some(elem);
+            }
+            self.front_leaf = next_leaf(&mut self.front, Iterator::next);
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        loop {
+            if let Some(elem) = self.back_leaf.next_back() {
+                return Some(elem);
+            }
+            self.back_leaf = next_leaf(&mut self.back, DoubleEndedIterator::next_back);
+        }
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<'a, T, const M: usize> IntoIterator for &'a Vector<T, M> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Node, Vector};
+    use crate::shared_list;
+    use std::vec::Vec;
+
+    // Checks the shape: leaves all at `height`, size tables right, and
+    // nothing but the root less than half full. Returns the length.
+    fn check_node<T, const M: usize>(node: &Node<T>, height: usize, root: bool) -> usize {
+        let width = match node {
+            Node::Leaf(elems) => {
+                assert_eq!(height, 0);
+                elems.len()
+            }
+            Node::Branch(branch) => {
+                assert!(height > 0);
+                let mut total = 0;
+                for (kid, &size) in branch.kids.iter().zip(&branch.sizes) {
+                    total += check_node::<T, M>(kid, height - 1, false);
+                    assert_eq!(size, total);
+                }
+                assert_eq!(branch.kids.len(), branch.sizes.len());
+                branch.kids.len()
+            }
+        };
+        assert!(width <= M);
+        assert!(width >= if root { 1 } else { M / 2 });
+        node.len()
+    }
+
+    fn check<T: Clone, const M: usize>(vector: &Vector<T, M>) -> Vec<T> {
+        match &vector.root {
+            Some(root) => {
+                assert_eq!(check_node::<T, M>(root, vector.height, true), vector.len);
+                if let Node::Branch(branch) = &**root {
+                    assert!(branch.kids.len() > 1);
+                }
+            }
+            None => assert_eq!(vector.len, 0),
+        }
+        let elems: Vec<T> = vector.iter().cloned().collect();
+        assert_eq!(elems.len(), vector.len());
+        elems
+    }
+
+    // Each shape test runs with a small branching factor, which builds deep
+    // trees from few elements, and with the real one. Sizes scale with M so
+    // both get several levels.
+
+    fn basics<const M: usize>() {
+        let n = 25 * M;
+        let empty: Vector<usize, M> = Vector::default();
+        assert_eq!(empty.get(0), None);
+        assert!(empty.iter().next().is_none());
+
+        let mut vector = empty.clone();
+        for i in 0..n {
+            vector = vector.push_back(i);
+            assert_eq!(check(&vector), (0..=i).collect::<Vec<_>>());
+        }
+        for i in 0..n {
+            assert_eq!(vector.get(i), Some(&i));
+        }
+        assert_eq!(vector.get(n), None);
+        assert!(vector.iter().rev().copied().eq((0..n).rev()));
+
+        let changed = vector.set(42, 0).set(n - 1, 0);
+        assert_eq!(check(&changed).len(), n);
+        assert_eq!(changed.get(42), Some(&0));
+        assert_eq!(changed.get(n - 1), Some(&0));
+        assert_eq!(vector.get(42), Some(&42));
+        assert_ne!(changed, vector);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn basics_small() {
+        basics::<4>();
+    }
+
+    #[test]
+    fn basics_real() {
+        basics::<32>();
+    }
+
+    fn from_iter_fills_evenly<const M: usize>() {
+        for n in 0..50 * M {
+            let vector: Vector<usize, M> = (0..n).collect();
+            assert_eq!(check(&vector), (0..n).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn from_iter_fills_evenly_small() {
+        from_iter_fills_evenly::<4>();
+    }
+
+    #[test]
+    fn from_iter_fills_evenly_real() {
+        from_iter_fills_evenly::<32>();
+    }
+
+    fn concat<const M: usize>() {
+        for a in 0..10 * M {
+            for b in [0, 1, 3, 7, 5 * M, 22 * M] {
+                let left: Vector<usize, M> = (0..a).collect();
+                let right: Vector<usize, M> = (a..a + b).collect();
+                let both = left.concat(&right);
+                assert_eq!(check(&both), (0..a + b).collect::<Vec<_>>());
+                assert_eq!(check(&right.concat(&left)).len(), a + b);
+            }
+        }
+
+        // Many small pieces still make a balanced tree.
+        let pieces = 125 * M;
+        let mut vector: Vector<usize, M> = Vector::default();
+        for i in 0..pieces {
+            vector = vector.concat(&(i * 3..i * 3 + 3).collect());
+        }
+        assert_eq!(check(&vector), (0..pieces * 3).collect::<Vec<_>>());
+        assert!(vector.height >= 2);
+    }
+
+    #[test]
+    fn concat_small() {
+        concat::<4>();
+    }
+
+    #[test]
+    fn concat_real() {
+        concat::<32>();
+    }
+
+    fn split_at<const M: usize>() {
+        let n = 38 * M;
+        let vector: Vector<usize, M> = (0..n).collect();
+        for i in 0..=n {
+            let (left, right) = vector.split_at(i);
+            assert_eq!(check(&left), (0..i).collect::<Vec<_>>());
+            assert_eq!(check(&right), (i..n).collect::<Vec<_>>());
+            assert_eq!(left.concat(&right), vector);
+        }
+        assert_eq!(check(&vector), (0..n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn split_at_small() {
+        split_at::<4>();
+    }
+
+    #[test]
+    fn split_at_real() {
+        split_at::<32>();
+    }
+
+    #[test]
+    #[should_panic(expected = "split index out of bounds")]
+    fn split_out_of_bounds() {
+        let vector: Vector<i32> = (0..5).collect();
+        vector.split_at(6);
+    }
+
+    #[test]
+    fn shared_list_round_trip() {
+        let list = shared_list::List::new().append(3).append(2).append(1);
+        let vector = Vector::from(&list);
+        assert_eq!(format!("{:?}", vector), "[1, 2, 3]");
+        let list = shared_list::List::from(&vector.push_back(4));
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4]);
+    }
+}
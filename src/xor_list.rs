@@ -0,0 +1,753 @@
+// A doubly linked list with one link word per node: each node stores the
+// XOR of its neighbours' addresses (0 standing in for "none"). Walking needs
+// the node you came from, which every walk starting at an end has, so both
+// ends push and pop in O(1) and iteration works from either end.
+//
+// What a node costs on a 64-bit target with Global, counting the heap block:
+//
+//   T = u32          XorList   sll::List   deque_book::List
+//   links            8         8           16 (next, prev)
+//   Rc + RefCell     -         -           16 (strong count, borrow flag)
+//   element+padding  8         8           8
+//   total            16        16          40
+//
+// so a deque of u32s takes 2.5x less memory this way, with the same
+// operations at the ends (xor_list's tests measure this). What it gives up:
+// there are no stable node handles, since a node alone can't tell you its
+// neighbours, and it's all raw pointers with no RefCell checks.
+//
+// The address casts are exposing casts, so turning the XORed addresses back
+// into pointers is allowed.
+
+use alloc::boxed::Box;
+use core::cmp::Ordering;
+use core::fmt;
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr::NonNull;
+
+use crate::allocator::{self, AllocError, Allocator, Global};
+use crate::merge_sort::{self, Run, Sortable};
+
+pub struct XorList<T, A: Allocator = Global> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    alloc: A,
+    _owns: PhantomData<Box<Node<T>>>,
+}
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    // Address of prev XOR address of next.
+    link: usize,
+    elem: T,
+}
+
+unsafe impl<T: Send, A: Allocator + Send> Send for XorList<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for XorList<T, A> {}
+
+fn addr<T>(node: Link<T>) -> usize {
+    node.map_or(0, |node| node.as_ptr() as usize)
+}
+
+// The neighbour of `node` on the other side from `from`.
+unsafe fn step<T>(from: Link<T>, node: NonNull<Node<T>>) -> Link<T> {
+    NonNull::new(((*node.as_ptr()).link ^ addr(from)) as *mut Node<T>)
+}
+
+impl<T> XorList<T> {
+    pub fn new() -> Self {
+        XorList::new_in(Global)
+    }
+}
+
+impl<T, A: Allocator> XorList<T, A> {
+    pub fn new_in(alloc: A) -> Self {
+        XorList {
+            head: None,
+            tail: None,
+            len: 0,
+            alloc,
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn push_front(&mut self, elem: T) {
+        let node = allocator::alloc_in(Node { link: 0, elem }, &self.alloc);
+        unsafe { self.link_front(node) };
+    }
+
+    pub fn push_back(&mut self, elem: T) {
+        let node = allocator::alloc_in(Node { link: 0, elem }, &self.alloc);
+        unsafe { self.link_back(node) };
+    }
+
+    // Like push_front, but hands the element back if the allocator can't
+    // provide a node.
+    pub fn try_push_front(&mut self, elem: T) -> Result<(), (T, AllocError)> {
+        let node = allocator::try_alloc_in(Node { link: 0, elem }, &self.alloc)
+            .map_err(|(node, err)| (node.elem, err))?;
+        unsafe { self.link_front(node) };
+        Ok(())
+    }
+
+    pub fn try_push_back(&mut self, elem: T) -> Result<(), (T, AllocError)> {
+        let node = allocator::try_alloc_in(Node { link: 0, elem }, &self.alloc)
+            .map_err(|(node, err)| (node.elem, err))?;
+        unsafe { self.link_back(node) };
+        Ok(())
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        unsafe { self.unlink_front() }
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        // Popping from the back is popping from the front of the reversed
+        // list.
+        self.reverse();
+        let elem = unsafe { self.unlink_front() };
+        self.reverse();
+        elem
+    }
+
+    pub fn peek_front(&self) -> Option<&T> {
+        unsafe { self.head.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_back(&self) -> Option<&T> {
+        unsafe { self.tail.map(|node| &(*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_front_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.head.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    pub fn peek_back_mut(&mut self) -> Option<&mut T> {
+        unsafe { self.tail.map(|node| &mut (*node.as_ptr()).elem) }
+    }
+
+    // O(1): the links read the same in both directions.
+    pub fn reverse(&mut self) {
+        core::mem::swap(&mut self.head, &mut self.tail);
+    }
+
+    unsafe fn link_front(&mut self, node: NonNull<Node<T>>) {
+        (*node.as_ptr()).link = addr(self.head);
+        match self.head {
+            // The old head's prev was none (0); now it's `node`.
+            Some(old_head) => (*old_head.as_ptr()).link ^= addr(Some(node)),
+            None => self.tail = Some(node),
+        }
+        self.head = Some(node);
+        self.len += 1;
+    }
+
+    unsafe fn link_back(&mut self, node: NonNull<Node<T>>) {
+        self.reverse();
+        self.link_front(node);
+        self.reverse();
+    }
+
+    unsafe fn unlink_front(&mut self) -> Option<T> {
+        let old_head = self.head?;
+        let next = step(None, old_head);
+        match next {
+            Some(next) => (*next.as_ptr()).link ^= addr(Some(old_head)),
+            None => self.tail = None,
+        }
+        self.head = next;
+        self.len -= 1;
+        Some(allocator::take_in(old_head, &self.alloc).elem)
+    }
+}
+
+/********** Sorting **********/
+
+impl<T, A: Allocator> XorList<T, A> {
+    // Sorts the list front to back as it currently reads, reversed or not;
+    // see merge_sort::sort_by.
+    pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, compare: F) {
+        merge_sort::sort_by(self, compare);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Send, A: Allocator> XorList<T, A> {
+    // merge_sort::par_sort_by, with a run per core.
+    pub fn par_sort_by<F>(&mut self, compare: F)
+    where
+        F: Fn(&T, &T) -> Ordering + Sync,
+    {
+        let len = self.len;
+        merge_sort::par_sort_by(self, len, merge_sort::par_runs(len), &compare);
+    }
+}
+
+// A stretch of nodes off a list, linked the same way, with none at either
+// end. Its nodes are freed by the list they go back to.
+pub(crate) struct Chain<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+}
+
+// Its nodes are reachable only through it.
+unsafe impl<T: Send> Send for Chain<T> {}
+
+impl<T> Chain<T> {
+    fn new() -> Self {
+        Chain {
+            head: None,
+            tail: None,
+            len: 0,
+        }
+    }
+
+    // Moves the first node onto the back of `to`.
+    fn move_front(&mut self, to: &mut Chain<T>) {
+        let rest = self.split_off(1);
+        let mut front = mem::replace(self, rest);
+        to.append(&mut front);
+    }
+}
+
+impl<T> Run for Chain<T> {
+    type Elem = T;
+
+    fn empty(&self) -> Self {
+        Chain::new()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn split_off(&mut self, at: usize) -> Self {
+        if at >= self.len {
+            return Chain::new();
+        }
+        if at == 0 {
+            return mem::replace(self, Chain::new());
+        }
+        let (mut from, mut last) = (None, self.head.unwrap());
+        for _ in 1..at {
+            let next = unsafe { step(from, last) }.unwrap();
+            from = Some(last);
+            last = next;
+        }
+        let first = unsafe { step(from, last) }.unwrap();
+        unsafe {
+            // Neither side of the cut has a neighbour across it any more.
+            (*last.as_ptr()).link ^= addr(Some(first));
+            (*first.as_ptr()).link ^= addr(Some(last));
+        }
+        let rest = Chain {
+            head: Some(first),
+            tail: self.tail,
+            len: self.len - at,
+        };
+        self.tail = Some(last);
+        self.len = at;
+        rest
+    }
+
+    fn append(&mut self, other: &mut Self) {
+        let other = mem::replace(other, Chain::new());
+        let other_head = match other.head {
+            Some(head) => head,
+            None => return,
+        };
+        match self.tail {
+            Some(tail) => unsafe {
+                (*tail.as_ptr()).link ^= addr(Some(other_head));
+                (*other_head.as_ptr()).link ^= addr(Some(tail));
+            },
+            None => self.head = Some(other_head),
+        }
+        self.tail = other.tail;
+        self.len += other.len;
+    }
+
+    fn merge<F: FnMut(&T, &T) -> Ordering>(&mut self, other: &mut Self, compare: &mut F) {
+        // Builds the result in `done`. If `compare` panics, this chain gets
+        // what was merged so far followed by its own leftovers, and `other`
+        // keeps the rest of its.
+        struct Merging<'a, T> {
+            chain: &'a mut Chain<T>,
+            done: Chain<T>,
+        }
+
+        impl<T> Drop for Merging<'_, T> {
+            fn drop(&mut self) {
+                self.done.append(self.chain);
+                mem::swap(self.chain, &mut self.done);
+            }
+        }
+
+        let mut m = Merging {
+            chain: self,
+            done: Chain::new(),
+        };
+        while let (Some(ours), Some(theirs)) = (m.chain.head, other.head) {
+            let theirs_first =
+                unsafe { compare(&(*theirs.as_ptr()).elem, &(*ours.as_ptr()).elem) } == Ordering::Less;
+            if theirs_first {
+                other.move_front(&mut m.done);
+            } else {
+                m.chain.move_front(&mut m.done);
+            }
+        }
+        m.done.append(other);
+    }
+}
+
+impl<T, A: Allocator> Sortable for XorList<T, A> {
+    type Run = Chain<T>;
+
+    fn take_run(&mut self) -> Chain<T> {
+        let len = mem::replace(&mut self.len, 0);
+        Chain {
+            head: self.head.take(),
+            tail: self.tail.take(),
+            len,
+        }
+    }
+
+    fn put_run(&mut self, run: Chain<T>) {
+        self.head = run.head;
+        self.tail = run.tail;
+        self.len = run.len;
+    }
+}
+
+#[cfg(feature = "nightly")]
+unsafe impl<#[may_dangle] T, A: Allocator> Drop for XorList<T, A> {
+    fn drop(&mut self) {
+        self.drop_nodes();
+    }
+}
+
+#[cfg(not(feature = "nightly"))]
+impl<T, A: Allocator> Drop for XorList<T, A> {
+    fn drop(&mut self) {
+        self.drop_nodes();
+    }
+}
+
+impl<T, A: Allocator> XorList<T, A> {
+    fn drop_nodes(&mut self) {
+        // Keeps popping during the unwind if an element's destructor panics,
+        // so the rest of the nodes are still freed.
+        struct DropGuard<'a, T, A: Allocator>(&'a mut XorList<T, A>);
+
+        impl<T, A: Allocator> Drop for DropGuard<'_, T, A> {
+            fn drop(&mut self) {
+                while self.0.pop_front().is_some() {}
+            }
+        }
+
+        let guard = DropGuard(self);
+        while guard.0.pop_front().is_some() {}
+    }
+}
+
+impl<T> Default for XorList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for XorList<T, A> {
+    fn clone(&self) -> Self {
+        let mut list = XorList::new_in(self.alloc.clone());
+        list.extend(self.iter().cloned());
+        list
+    }
+}
+
+impl<T, A: Allocator> Extend<T> for XorList<T, A> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for elem in iter {
+            self.push_back(elem);
+        }
+    }
+}
+
+impl<T> FromIterator<T> for XorList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = XorList::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for XorList<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/********** Iterators **********/
+
+// A walk from each end. Each end remembers the node it came from, which is
+// all a XOR link needs; `len` says when the two walks have met.
+struct Walk<T> {
+    front: (Link<T>, Link<T>),
+    back: (Link<T>, Link<T>),
+    len: usize,
+}
+
+impl<T> Walk<T> {
+    fn new<A: Allocator>(list: &XorList<T, A>) -> Self {
+        Walk {
+            front: (None, list.head),
+            back: (None, list.tail),
+            len: list.len,
+        }
+    }
+
+    fn advance(end: &mut (Link<T>, Link<T>), len: &mut usize) -> Option<NonNull<Node<T>>> {
+        if *len == 0 {
+            return None;
+        }
+        *len -= 1;
+        let (from, node) = *end;
+        let node = node?;
+        *end = (Some(node), unsafe { step(from, node) });
+        Some(node)
+    }
+
+    fn next(&mut self) -> Option<NonNull<Node<T>>> {
+        Self::advance(&mut self.front, &mut self.len)
+    }
+
+    fn next_back(&mut self) -> Option<NonNull<Node<T>>> {
+        Self::advance(&mut self.back, &mut self.len)
+    }
+}
+
+pub struct Iter<'a, T> {
+    walk: Walk<T>,
+    _marker: PhantomData<&'a T>,
+}
+
+unsafe impl<T: Sync> Send for Iter<'_, T> {}
+unsafe impl<T: Sync> Sync for Iter<'_, T> {}
+
+impl<T, A: Allocator> XorList<T, A> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            walk: Walk::new(self),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.walk.next().map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.walk.len, Some(self.walk.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.walk.next_back().map(|node| unsafe { &(*node.as_ptr()).elem })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    walk: Walk<T>,
+    _marker: PhantomData<&'a mut T>,
+}
+
+unsafe impl<T: Send> Send for IterMut<'_, T> {}
+unsafe impl<T: Sync> Sync for IterMut<'_, T> {}
+
+impl<T, A: Allocator> XorList<T, A> {
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            walk: Walk::new(self),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.walk.next().map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.walk.len, Some(self.walk.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        self.walk.next_back().map(|node| unsafe { &mut (*node.as_ptr()).elem })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T, A: Allocator = Global>(XorList<T, A>);
+
+impl<T, A: Allocator> IntoIterator for XorList<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
+
+    fn into_iter(self) -> IntoIter<T, A> {
+        IntoIter(self)
+    }
+}
+
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T, A: Allocator> ExactSizeIterator for IntoIter<T, A> {}
+
+#[cfg(test)]
+mod test {
+    use super::XorList;
+    use crate::allocator::test::{Counting, Failing};
+    use crate::allocator::{AllocError, Allocator};
+    use crate::merge_sort;
+    use crate::panicky::{panics, Bomb};
+    use crate::{deque_book, sll};
+    use core::cell::Cell;
+    use core::cmp::Ordering;
+    use core::mem::size_of;
+    use std::vec::Vec;
+
+    #[test]
+    fn basics() {
+        let mut list = XorList::new();
+        assert_eq!(list.pop_front(), None);
+        assert_eq!(list.pop_back(), None);
+        assert_eq!(list.peek_front(), None);
+
+        list.push_back(2);
+        list.push_front(1);
+        list.push_back(3);
+        assert_eq!(list.len(), 3);
+        assert_eq!(list.peek_front(), Some(&1));
+        assert_eq!(list.peek_back(), Some(&3));
+        *list.peek_back_mut().unwrap() = 30;
+        *list.peek_front_mut().unwrap() = 10;
+
+        assert_eq!(list.pop_back(), Some(30));
+        assert_eq!(list.pop_front(), Some(10));
+        assert_eq!(list.pop_back(), Some(2));
+        assert_eq!(list.pop_front(), None);
+        assert!(list.is_empty());
+
+        list.push_front(4);
+        assert_eq!(list.pop_back(), Some(4));
+        assert_eq!(list.peek_back(), None);
+    }
+
+    #[test]
+    fn iterators() {
+        let mut list: XorList<i32> = (1..=6).collect();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(list.iter().rev().copied().collect::<Vec<_>>(), [6, 5, 4, 3, 2, 1]);
+
+        let mut iter = list.iter();
+        assert_eq!(iter.len(), 6);
+        assert_eq!(iter.next(), Some(&1));
+        assert_eq!(iter.next_back(), Some(&6));
+        assert_eq!(iter.next_back(), Some(&5));
+        assert_eq!(iter.next(), Some(&2));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next(), Some(&3));
+        assert_eq!(iter.next_back(), Some(&4));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+
+        for (i, elem) in list.iter_mut().rev().enumerate() {
+            *elem *= i as i32;
+        }
+        assert_eq!(format!("{:?}", list), "[5, 8, 9, 8, 5, 0]");
+
+        list.reverse();
+        assert_eq!(list.iter().copied().collect::<Vec<_>>(), [0, 5, 8, 9, 8, 5]);
+        list.push_front(-1);
+        list.push_back(-2);
+        let mut into = list.clone().into_iter();
+        assert_eq!(into.next_back(), Some(-2));
+        assert_eq!(into.collect::<Vec<_>>(), [-1, 0, 5, 8, 9, 8, 5]);
+        assert_eq!(list.len(), 8);
+    }
+
+    #[test]
+    fn allocator() {
+        let counting = Counting::default();
+        let mut list = XorList::new_in(&counting);
+        list.extend(0..10);
+        assert_eq!(counting.live(), 10);
+        list.pop_back();
+        list.pop_front();
+        assert_eq!(counting.live(), 8);
+        drop(list);
+        assert_eq!(counting.live(), 0);
+
+        let failing = Failing::new(1);
+        let mut list = XorList::new_in(&failing);
+        assert_eq!(list.try_push_back(1), Ok(()));
+        assert_eq!(list.try_push_front(2), Err((2, AllocError)));
+        assert_eq!(list.try_push_back(3), Err((3, AllocError)));
+        assert_eq!(list.pop_front(), Some(1));
+        assert_eq!(list.try_push_front(4), Ok(()));
+        assert_eq!(list.peek_back(), Some(&4));
+    }
+
+    // Heap bytes per node for 1000 u32s, measured at the allocator.
+    #[test]
+    fn per_node_overhead() {
+        const N: usize = 1000;
+        let word = size_of::<usize>();
+
+        let counting = Counting::default();
+        let mut xor = XorList::new_in(&counting);
+        xor.extend(0..N as u32);
+        let xor_bytes = counting.bytes.get() / N;
+        drop(xor);
+
+        let counting = Counting::default();
+        let mut sll = sll::List::new_in(&counting);
+        for i in 0..N as u32 {
+            sll.push(i);
+        }
+        let sll_bytes = counting.bytes.get() / N;
+        drop(sll);
+
+        let counting = Counting::default();
+        let mut deque = deque_book::List::new_in(&counting);
+        for i in 0..N as u32 {
+            deque.push_back(i);
+        }
+        let deque_bytes = counting.bytes.get() / N;
+        drop(deque);
+
+        // One link word plus the element padded out to a word: as small as a
+        // singly linked node.
+        assert_eq!(xor_bytes, 2 * word);
+        assert_eq!(xor_bytes, sll_bytes);
+        // next, prev, the Rc count and the RefCell flag, plus the element.
+        // Both links also carry a copy of the allocator, which is a word
+        // for &Counting and nothing for Global.
+        assert_eq!(deque_bytes, 5 * word + 2 * size_of::<&Counting>());
+    }
+
+    impl<E, A: Allocator> merge_sort::test::Subject<E, A> for XorList<E, A> {
+        fn make(elems: Vec<E>, alloc: A) -> Self {
+            let mut list = XorList::new_in(alloc);
+            list.extend(elems);
+            list
+        }
+
+        fn read<R>(&self, f: impl FnMut(&E) -> R) -> Vec<R> {
+            // The links read the same both ways.
+            let nodes: Vec<*const E> = self.iter().map(|x| x as *const E).collect();
+            let mut back: Vec<*const E> = self.iter().rev().map(|x| x as *const E).collect();
+            back.reverse();
+            assert_eq!(nodes, back);
+            assert_eq!(nodes.len(), self.len());
+            self.iter().map(f).collect()
+        }
+
+        fn sort_by(&mut self, compare: impl FnMut(&E, &E) -> Ordering) {
+            XorList::sort_by(self, compare);
+        }
+
+        #[cfg(feature = "std")]
+        fn par_sort_by(&mut self, compare: impl Fn(&E, &E) -> Ordering + Sync)
+        where
+            E: Send,
+            A: Send,
+        {
+            XorList::par_sort_by(self, compare);
+        }
+    }
+
+    #[test]
+    fn sort_by() {
+        merge_sort::test::sort_by::<XorList<_, _>>(&Counting::default());
+
+        // Sorting a reversed list sorts it front to back as it now reads.
+        let mut list: XorList<u32> =
+            (0..100u32).map(|i| i.wrapping_mul(2_654_435_761) % 97).collect();
+        list.reverse();
+        list.sort_by(|a, b| a.cmp(b));
+        assert!(list.iter().zip(list.iter().skip(1)).all(|(a, b)| a <= b));
+    }
+
+    #[test]
+    fn sort_by_survives_panics() {
+        merge_sort::test::sort_by_survives_panics::<XorList<_, _>>(&Counting::default());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn par_sort_by() {
+        merge_sort::test::par_sort_by::<XorList<_>>();
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn par_sort_by_survives_panics() {
+        merge_sort::test::par_sort_by_survives_panics::<XorList<_>>();
+    }
+
+    #[test]
+    fn drop_survives_panicking_destructor() {
+        let drops = Cell::new(0);
+        let mut list = XorList::new();
+        for i in 0..5 {
+            list.push_back(if i == 2 { Bomb::armed(&drops) } else { Bomb::new(&drops) });
+        }
+        assert!(panics(move || drop(list)));
+        assert_eq!(drops.get(), 5);
+    }
+}
@@ -0,0 +1,209 @@
+// A position inside a shared_list::List that can move and edit in O(1),
+// e.g. a cursor in an editor buffer. The elements before the focus are kept
+// in a list of their own, nearest first, and the focus is the head of the
+// list of everything from it on, which is shared with the list the zipper
+// came from until an edit reaches it.
+//
+// Like the lists, every move and edit gives a new zipper and leaves this
+// one as it was. The focus can also sit just past the last element, where
+// insert adds to the end and there's nothing to get, set or delete.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::shared_list::List;
+
+pub struct Zipper<T> {
+    // Reversed: the element just before the focus is the head.
+    left: List<T>,
+    right: List<T>,
+    index: usize,
+}
+
+impl<T> Zipper<T> {
+    // Focuses on the list's head.
+    pub fn new(list: &List<T>) -> Self {
+        Zipper {
+            left: List::new(),
+            right: list.clone(),
+            index: 0,
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        self.right.head()
+    }
+
+    // How many elements come before the focus.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.right.head().is_none()
+    }
+
+    // Adds `elem` just before the focus and focuses on it, so delete undoes
+    // it.
+    pub fn insert(&self, elem: T) -> Zipper<T> {
+        Zipper {
+            left: self.left.clone(),
+            right: self.right.append(elem),
+            index: self.index,
+        }
+    }
+
+    // Removes the focus; what came after it becomes the focus. None at the
+    // end.
+    pub fn delete(&self) -> Option<Zipper<T>> {
+        self.right.head()?;
+        Some(Zipper {
+            left: self.left.clone(),
+            right: self.right.tail(),
+            index: self.index,
+        })
+    }
+}
+
+impl<T: Clone> Zipper<T> {
+    // Focuses on the element before this one. None at the start.
+    pub fn left(&self) -> Option<Zipper<T>> {
+        let elem = self.left.head()?.clone();
+        Some(Zipper {
+            left: self.left.tail(),
+            right: self.right.append(elem),
+            index: self.index - 1,
+        })
+    }
+
+    // Focuses on the element after this one, or on the end after the last
+    // one. None at the end.
+    pub fn right(&self) -> Option<Zipper<T>> {
+        let elem = self.right.head()?.clone();
+        Some(Zipper {
+            left: self.left.append(elem),
+            right: self.right.tail(),
+            index: self.index + 1,
+        })
+    }
+
+    // Replaces the focus. None at the end.
+    pub fn set(&self, elem: T) -> Option<Zipper<T>> {
+        Some(self.delete()?.insert(elem))
+    }
+
+    // The whole list, edits and all. Copies the elements before the focus
+    // and shares the rest.
+    pub fn to_list(&self) -> List<T> {
+        self.left
+            .iter()
+            .fold(self.right.clone(), |list, elem| list.append(elem.clone()))
+    }
+}
+
+impl<T> Clone for Zipper<T> {
+    fn clone(&self) -> Self {
+        Zipper {
+            left: self.left.clone(),
+            right: self.right.clone(),
+            index: self.index,
+        }
+    }
+}
+
+impl<T> From<&List<T>> for Zipper<T> {
+    fn from(list: &List<T>) -> Self {
+        Zipper::new(list)
+    }
+}
+
+// Shows the elements in order with the focus in brackets.
+impl<T: fmt::Debug> fmt::Debug for Zipper<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Focus<'a, T>(Option<&'a T>);
+
+        impl<T: fmt::Debug> fmt::Debug for Focus<'_, T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self.0 {
+                    Some(elem) => write!(f, "[{:?}]", elem),
+                    None => f.write_str("[]"),
+                }
+            }
+        }
+
+        let mut list = f.debug_list();
+        let mut before: Vec<&T> = self.left.iter().collect();
+        before.reverse();
+        list.entries(before);
+        list.entry(&Focus(self.right.head()));
+        list.entries(self.right.iter().skip(1)).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Zipper;
+    use crate::shared_list::List;
+    use std::vec::Vec;
+
+    fn list(elems: &[char]) -> List<char> {
+        elems.iter().rev().fold(List::new(), |list, &elem| list.append(elem))
+    }
+
+    fn text(list: &List<char>) -> Vec<char> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn moves() {
+        let zipper = Zipper::new(&list(&['a', 'b', 'c']));
+        assert_eq!(zipper.get(), Some(&'a'));
+        assert!(zipper.left().is_none());
+
+        let end = zipper.right().unwrap().right().unwrap().right().unwrap();
+        assert!(end.at_end());
+        assert_eq!(end.index(), 3);
+        assert_eq!(end.get(), None);
+        assert!(end.right().is_none());
+        assert!(end.delete().is_none());
+        assert!(end.set('x').is_none());
+
+        let back = end.left().unwrap().left().unwrap();
+        assert_eq!(back.get(), Some(&'b'));
+        assert_eq!(back.index(), 1);
+        assert_eq!(format!("{:?}", back), "['a', ['b'], 'c']");
+        assert_eq!(format!("{:?}", end), "['a', 'b', 'c', []]");
+
+        let empty = Zipper::<char>::new(&List::new());
+        assert!(empty.at_end());
+        assert!(empty.left().is_none());
+        assert!(empty.right().is_none());
+    }
+
+    #[test]
+    fn edits() {
+        let original = list(&['c', 'a', 't']);
+        let zipper = Zipper::from(&original);
+
+        let cart = zipper.right().unwrap().right().unwrap().insert('r');
+        assert_eq!(cart.get(), Some(&'r'));
+        assert_eq!(text(&cart.to_list()), ['c', 'a', 'r', 't']);
+
+        let card = cart.right().unwrap().set('d').unwrap();
+        assert_eq!(text(&card.to_list()), ['c', 'a', 'r', 'd']);
+
+        let car = card.delete().unwrap();
+        assert!(car.at_end());
+        let cars = car.insert('s');
+        assert_eq!(text(&cars.to_list()), ['c', 'a', 'r', 's']);
+
+        let ars = Zipper::from(&cars.to_list()).delete().unwrap();
+        assert_eq!(text(&ars.to_list()), ['a', 'r', 's']);
+
+        // Every version is still there.
+        assert_eq!(text(&original), ['c', 'a', 't']);
+        assert_eq!(text(&zipper.to_list()), ['c', 'a', 't']);
+        assert_eq!(text(&cart.to_list()), ['c', 'a', 'r', 't']);
+        assert_eq!(text(&car.to_list()), ['c', 'a', 'r']);
+    }
+}
@@ -0,0 +1,17 @@
+// Compile-time checks of variance, Send/Sync and drop check for the lists.
+// After a compiler upgrade changes the wording of an error, refresh the
+// expected output with:
+//   TRYBUILD=overwrite cargo test --test ui
+// and run the nightly-only cases with:
+//   cargo +nightly test --features nightly --test ui
+
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+    if cfg!(feature = "nightly") {
+        t.pass("tests/ui/nightly/pass/*.rs");
+        t.compile_fail("tests/ui/nightly/fail/*.rs");
+    }
+}
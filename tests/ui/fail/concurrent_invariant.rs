@@ -0,0 +1,16 @@
+// The concurrent lists are pushed to through &self. If they were covariant,
+// a Stack<&'static str> seen as a Stack<&'a str> could be handed a short-lived
+// &str that the owner then pops out as &'static str.
+
+use lists::ms_queue::Queue;
+use lists::treiber::Stack;
+
+fn stack<'s, 'a>(stack: &'s Stack<&'static str>) -> &'s Stack<&'a str> {
+    stack
+}
+
+fn queue<'s, 'a>(queue: &'s Queue<&'static str>) -> &'s Queue<&'a str> {
+    queue
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+// deque_book keeps its nodes in RefCells, which makes it invariant in T.
+
+use lists::deque_book::List;
+
+fn shrink<'a>(list: List<&'static str>) -> List<&'a str> {
+    list
+}
+
+fn main() {}
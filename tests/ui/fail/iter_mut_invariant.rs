@@ -0,0 +1,17 @@
+// Like &mut T, a mutable iterator must be invariant in the element type, or
+// it could write a short-lived reference into a list of longer-lived ones.
+
+use lists::sll::ListIterMut;
+use lists::static_list::StaticListIterMut;
+
+fn sll<'i, 'a>(iter: ListIterMut<'i, &'static str>) -> ListIterMut<'i, &'a str> {
+    iter
+}
+
+fn static_list<'i, 'a>(
+    iter: StaticListIterMut<'i, &'static str, 4>,
+) -> StaticListIterMut<'i, &'a str, 4> {
+    iter
+}
+
+fn main() {}
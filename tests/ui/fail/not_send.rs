@@ -0,0 +1,24 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use lists::static_list::StaticList;
+use lists::treiber::Stack;
+use lists::{deque_book, shared_list, sll};
+
+fn send<T: Send>() {}
+fn sync<T: Sync>() {}
+
+fn main() {
+    // Rc-linked lists stay on the thread that built them.
+    send::<deque_book::List<i32>>();
+    send::<shared_list::List<i32>>();
+
+    // The others are only as Send as their elements...
+    send::<sll::List<Rc<i32>>>();
+    send::<StaticList<Rc<i32>, 4>>();
+    send::<Stack<Rc<i32>>>();
+
+    // ...and only as Sync as their elements, for anything that shares them.
+    sync::<sll::List<Cell<i32>>>();
+    send::<sll::ListIter<'static, Cell<i32>>>();
+}
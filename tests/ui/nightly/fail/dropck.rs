@@ -0,0 +1,16 @@
+// #[may_dangle] doesn't switch dropck off: an element whose destructor uses
+// the borrowed data still has to be dropped first.
+
+struct Loud<'a>(&'a String);
+
+impl Drop for Loud<'_> {
+    fn drop(&mut self) {
+        println!("{}", self.0);
+    }
+}
+
+fn main() {
+    let mut list = lists::sll::List::new();
+    let s = String::from("borrowed");
+    list.push(Loud(&s));
+}
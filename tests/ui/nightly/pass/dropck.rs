@@ -0,0 +1,33 @@
+// With #[may_dangle], a list can be dropped after the data its elements
+// borrow, as long as the elements don't look at it while being dropped.
+
+use lists::ms_queue::Queue;
+use lists::ring::Ring;
+use lists::static_list::{StaticDeque, StaticList};
+use lists::treiber::Stack;
+use lists::xor_list::XorList;
+use lists::{deque_book, sll};
+
+fn main() {
+    let mut sll = sll::List::new();
+    let mut deque = deque_book::List::with_pool(2);
+    let mut static_list = StaticList::<_, 2>::new();
+    let mut static_deque = StaticDeque::<_, 2>::new();
+    let mut ring = Ring::new();
+    let mut xor = XorList::new();
+    let stack = Stack::new();
+    let queue = Queue::new();
+
+    // Declared last, so dropped before the lists.
+    let s = String::from("borrowed");
+    sll.push(&s);
+    deque.push_back(&s);
+    deque.push_back(&s);
+    deque.pop_back();
+    static_list.push(&s).unwrap();
+    static_deque.push_back(&s).unwrap();
+    ring.insert_here(&s);
+    xor.push_back(&s);
+    stack.push(&s);
+    queue.push(&s);
+}
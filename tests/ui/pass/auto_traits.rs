@@ -0,0 +1,57 @@
+// Lists that don't share nodes are Send and Sync whenever their elements are,
+// and their iterators follow the &T / &mut T they hand out.
+
+use std::cell::Cell;
+
+use lists::ms_queue::Queue;
+use lists::ring::{self, Ring};
+use lists::sll;
+use lists::static_list::{
+    StaticDeque, StaticDequeIter, StaticDequeIterMut, StaticList, StaticListIter,
+    StaticListIterMut,
+};
+use lists::treiber::Stack;
+use lists::xor_list::{self, XorList};
+
+fn send<T: Send>() {}
+fn sync<T: Sync>() {}
+
+fn main() {
+    send::<sll::List<i32>>();
+    sync::<sll::List<i32>>();
+    send::<sll::ListIntoIter<i32>>();
+    send::<sll::ListIter<'static, i32>>();
+    sync::<sll::ListIter<'static, i32>>();
+    send::<sll::ListIterAlt<'static, i32>>();
+    send::<sll::ListIterMut<'static, i32>>();
+    sync::<sll::ListIterMut<'static, i32>>();
+    // A &mut iterator only moves its elements, so they needn't be Sync.
+    send::<sll::ListIterMut<'static, Cell<i32>>>();
+
+    send::<Ring<i32>>();
+    sync::<Ring<i32>>();
+    send::<ring::Iter<'static, i32>>();
+    send::<ring::IntoIter<i32>>();
+
+    send::<XorList<i32>>();
+    sync::<XorList<i32>>();
+    send::<xor_list::Iter<'static, i32>>();
+    send::<xor_list::IterMut<'static, Cell<i32>>>();
+    send::<xor_list::IntoIter<i32>>();
+
+    send::<StaticList<i32, 4>>();
+    sync::<StaticList<i32, 4>>();
+    send::<StaticListIter<'static, i32, 4>>();
+    send::<StaticListIterMut<'static, Cell<i32>, 4>>();
+    send::<StaticDeque<i32, 4>>();
+    sync::<StaticDeque<i32, 4>>();
+    send::<StaticDequeIter<'static, i32, 4>>();
+    send::<StaticDequeIterMut<'static, Cell<i32>, 4>>();
+
+    // The concurrent lists hand elements between threads but never share
+    // them, so elements only need to be Send.
+    send::<Stack<Cell<i32>>>();
+    sync::<Stack<Cell<i32>>>();
+    send::<Queue<Cell<i32>>>();
+    sync::<Queue<Cell<i32>>>();
+}
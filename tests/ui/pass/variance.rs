@@ -0,0 +1,86 @@
+// Lists that own their elements are covariant in them, like Vec, and so are
+// their shared and by-value iterators.
+
+use lists::static_list::{StaticDeque, StaticDequeIter, StaticList, StaticListIter};
+use lists::ring::{self, Ring};
+use lists::xor_list::{self, XorList};
+use lists::{shared_list, sll};
+
+fn sll<'a>(list: sll::List<&'static str>) -> sll::List<&'a str> {
+    list
+}
+
+fn sll_into_iter<'a>(iter: sll::ListIntoIter<&'static str>) -> sll::ListIntoIter<&'a str> {
+    iter
+}
+
+fn sll_iter<'i, 'a>(iter: sll::ListIter<'i, &'static str>) -> sll::ListIter<'i, &'a str> {
+    iter
+}
+
+fn sll_iter_alt<'i, 'a>(
+    iter: sll::ListIterAlt<'i, &'static str>,
+) -> sll::ListIterAlt<'i, &'a str> {
+    iter
+}
+
+fn shared_list<'a>(list: shared_list::List<&'static str>) -> shared_list::List<&'a str> {
+    list
+}
+
+fn shared_list_iter<'i, 'a>(
+    iter: shared_list::ListIter<'i, &'static str>,
+) -> shared_list::ListIter<'i, &'a str> {
+    iter
+}
+
+fn static_list<'a>(list: StaticList<&'static str, 4>) -> StaticList<&'a str, 4> {
+    list
+}
+
+fn static_list_iter<'i, 'a>(
+    iter: StaticListIter<'i, &'static str, 4>,
+) -> StaticListIter<'i, &'a str, 4> {
+    iter
+}
+
+fn static_deque<'a>(deque: StaticDeque<&'static str, 4>) -> StaticDeque<&'a str, 4> {
+    deque
+}
+
+fn static_deque_iter<'i, 'a>(
+    iter: StaticDequeIter<'i, &'static str, 4>,
+) -> StaticDequeIter<'i, &'a str, 4> {
+    iter
+}
+
+fn xor_list<'a>(list: XorList<&'static str>) -> XorList<&'a str> {
+    list
+}
+
+fn xor_list_iter<'i, 'a>(iter: xor_list::Iter<'i, &'static str>) -> xor_list::Iter<'i, &'a str> {
+    iter
+}
+
+fn xor_list_into_iter<'a>(iter: xor_list::IntoIter<&'static str>) -> xor_list::IntoIter<&'a str> {
+    iter
+}
+
+fn ring<'a>(ring: Ring<&'static str>) -> Ring<&'a str> {
+    ring
+}
+
+fn ring_iter<'i, 'a>(iter: ring::Iter<'i, &'static str>) -> ring::Iter<'i, &'a str> {
+    iter
+}
+
+fn ring_into_iter<'a>(iter: ring::IntoIter<&'static str>) -> ring::IntoIter<&'a str> {
+    iter
+}
+
+// Every iterator may give up some of the time it borrows the list for.
+fn sll_iter_mut<'a>(iter: sll::ListIterMut<'static, i32>) -> sll::ListIterMut<'a, i32> {
+    iter
+}
+
+fn main() {}